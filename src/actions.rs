@@ -6,7 +6,9 @@ pub enum Action {
     ChangeMod,
     RunGame,
     UseDefaultSettings,
+    VerifyVanilla,
     CreateNewModFolder,
+    RescanLibrary,
     ChangeConfigurationPath,
     ClearConfiguration,
     Exit,
@@ -17,7 +19,9 @@ impl Action {
         Action::ChangeMod,
         Action::RunGame,
         Action::UseDefaultSettings,
+        Action::VerifyVanilla,
         Action::CreateNewModFolder,
+        Action::RescanLibrary,
         Action::ChangeConfigurationPath,
         Action::ClearConfiguration,
         Action::Exit,
@@ -30,7 +34,9 @@ impl Display for Action {
             Action::ChangeMod => write!(f, "Change the current mod."),
             Action::RunGame => write!(f, "Run Hotline Miami 2."),
             Action::UseDefaultSettings => write!(f, "Use the default setting's (Normal game music without mods)."),
+            Action::VerifyVanilla => write!(f, "Check that the game is vanilla (for speedruns and leaderboards)."),
             Action::CreateNewModFolder => write!(f, "Create a new mod folder structure."),
+            Action::RescanLibrary => write!(f, "Rescan your mods folder."),
             Action::ChangeConfigurationPath => write!(f, "Change one of your paths."),
             Action::ClearConfiguration => write!(f, "Clear your configuration."),
             Action::Exit => write!(f, "Exit."),
@@ -1,29 +1,39 @@
 use std::path::PathBuf;
 
-use inquire::{error::InquireResult, Select};
+use inquire::error::InquireResult;
 
 use crate::{
     configs::paths_config::{ConfigurationPath, WithPath, WithoutPath},
-    functions::get_user_input,
+    functions::{get_user_input, prompt_user_select},
+    prompter::Prompter,
 };
 
-pub fn get_desired_path_to_change() -> InquireResult<ConfigurationPath<WithPath>> {
+pub fn get_desired_path_to_change(
+    prompter: &mut dyn Prompter,
+) -> InquireResult<ConfigurationPath<WithPath>> {
     let variants = Vec::from(ConfigurationPath::VARIANTS);
-    let configuration_path =
-        Select::new("What folder's path do you want to change?", variants).prompt()?;
-    validate_path(configuration_path)
+    let configuration_path = prompt_user_select(
+        prompter,
+        "What folder's path do you want to change?",
+        variants,
+    )?;
+    validate_path(prompter, configuration_path)
 }
 
 fn validate_path(
+    prompter: &mut dyn Prompter,
     configuration_path: ConfigurationPath<WithoutPath>,
 ) -> InquireResult<ConfigurationPath<WithPath>> {
     let path = get_user_input(
-        &format!("The path for your {} folder:", configuration_path.name())
-    );
+        prompter,
+        &format!("The path for your {} folder:", configuration_path.name()),
+    )?;
     if std::fs::metadata(&path).is_ok() {
         Ok(configuration_path.with_path(PathBuf::from(path)))
     } else {
-        println!("Couldn't validate this path: {}. Please try again.", &path);
-        validate_path(configuration_path)
+        prompter.warn(&format_args!(
+            "Couldn't validate this path: {path}. Please try again."
+        ));
+        validate_path(prompter, configuration_path)
     }
 }
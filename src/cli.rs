@@ -0,0 +1,326 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use indicatif::HumanBytes;
+
+use crate::{
+    configs::{
+        config_dir::ConfigDir,
+        current_mod_config::CurrentMod,
+        paths_config::{
+            ConfigPath, ConfigurationPath, GamePath, ModsGroupPath, ModsPath, ProgramPath,
+        },
+        ConfigsError,
+    },
+    manager::print_mod_name,
+    mod_manager::{ModManager, SwitchReport},
+    prompter::InquirePrompter,
+    run_game::run_hotline_miami_2,
+    tui::Tui,
+    wad::unpack::{pack, unpack},
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Manage and switch between your Hotline Miami 2 mods."
+)]
+pub struct Cli {
+    /// Keep the configuration in this folder instead of the default one.
+    #[arg(long, global = true, value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Cli {
+    pub fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+
+    pub fn config_dir(&self) -> Result<ConfigDir, ConfigsError> {
+        ConfigDir::resolve(self.config_dir.as_deref())
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Switch to the mod with the given folder or display name.
+    Switch {
+        name: String,
+        /// Show what the switch would do without changing any file.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List every mod in your mods group folder.
+    List,
+    /// Show the mod currently in use and the configured paths.
+    Status,
+    /// Use the default settings (normal game music without mods).
+    Default,
+    /// Check that the game is vanilla: no patchwads and the original music.
+    Verify {
+        /// Put the original music back and move the patchwads aside when the check fails.
+        #[arg(long)]
+        restore: bool,
+    },
+    /// Merge the patchwads of a mod into one, keeping the originals in a backup folder.
+    Merge {
+        name: String,
+        /// The patchwads in load order, separated by commas. A later one wins when several provide
+        /// the same asset. Defaults to name order, like the game.
+        #[arg(long, value_delimiter = ',')]
+        order: Vec<String>,
+    },
+    /// List, compare or export the tracks of a music wad.
+    Music {
+        #[command(subcommand)]
+        command: MusicCommand,
+    },
+    /// Build a music wad from the original music with some tracks taken from other mods or files,
+    /// as the music of a new or existing mod.
+    Compose {
+        name: String,
+        /// A track and where to take it from: a mod, MOD:TRACK for a differently named track, or
+        /// an audio file. Can be repeated.
+        #[arg(long = "track", value_name = "TRACK=SOURCE", required = true)]
+        tracks: Vec<String>,
+        /// Start from this mod's music instead of the original music.
+        #[arg(long, value_name = "MOD")]
+        base: Option<String>,
+    },
+    /// Create a new mod folder structure with the given snake case name.
+    Create { name: String },
+    /// Run Hotline Miami 2 through Steam.
+    Run,
+    /// Extract the files of a wad or patchwad into a new folder.
+    Unpack { file: PathBuf, dir: PathBuf },
+    /// Build a wad or patchwad from a folder, keeping the layout of the one it was unpacked from.
+    Pack { dir: PathBuf, file: PathBuf },
+    /// Read or change one of your paths.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Clear your configuration.
+    Clear,
+    /// Open a full-screen interface with your mods, their details and the mod in use.
+    Tui,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print one path, or all of them when no key is given.
+    Get { key: Option<ConfigKey> },
+    /// Change one of your paths.
+    Set { key: ConfigKey, path: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MusicCommand {
+    /// List the tracks in a mod's music, or in the game's music when no mod is given.
+    Tracks { name: Option<String> },
+    /// Show which tracks a mod's music replaces compared with the original music, or the game's
+    /// music when no mod is given.
+    Diff { name: Option<String> },
+    /// Write one track to a new file.
+    Export {
+        track: String,
+        file: PathBuf,
+        /// Take the track from this mod's music instead of the game's.
+        #[arg(long = "mod", value_name = "NAME")]
+        name: Option<String>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ConfigKey {
+    #[value(name = "game_path")]
+    Game,
+    #[value(name = "mods_path")]
+    Mods,
+    #[value(name = "mods_group_path")]
+    Group,
+}
+
+impl Command {
+    pub fn execute(&self, config_dir: ConfigDir) -> anyhow::Result<()> {
+        match self {
+            Command::Run => return Ok(run_hotline_miami_2()?),
+            Command::Unpack { file, dir } => {
+                let wad = unpack(file, dir)?;
+                println!("Unpacked {} files into {}", wad.entries().len(), dir.display());
+                return Ok(());
+            }
+            Command::Pack { dir, file } => {
+                let wad = pack(dir, file)?;
+                println!("Packed {} files into {}", wad.entries().len(), file.display());
+                return Ok(());
+            }
+            // Asks for the paths before taking over the terminal, like the menu does.
+            Command::Tui => {
+                let manager = ModManager::build(config_dir, &mut InquirePrompter)?;
+                print_config_warnings(&manager);
+                return Ok(Tui::new(manager).run()?);
+            }
+            _ => {}
+        }
+
+        let mut manager = ModManager::load(config_dir)?;
+        print_config_warnings(&manager);
+
+        match self {
+            Command::Switch {
+                name,
+                dry_run: true,
+            } => print!("{}", manager.plan_switch_by_name(name)?),
+            Command::Switch {
+                name,
+                dry_run: false,
+            } => {
+                let report = manager.switch_mod_by_name(name)?;
+                print_warnings(&report);
+            }
+            Command::List => print_mods(&manager),
+            Command::Status => {
+                print_mod_name(&mut manager);
+                print_paths(&manager);
+            }
+            Command::Default => print_warnings(&manager.use_default_settings()?),
+            Command::Verify { restore } => verify_vanilla(&mut manager, *restore)?,
+            Command::Merge { name, order } => print!("{}", manager.merge_patchwads(name, order)?),
+            Command::Music { command } => execute_music(&manager, command)?,
+            Command::Compose { name, tracks, base } => {
+                print!("{}", manager.compose_music(name, base.as_deref(), tracks)?);
+            }
+            Command::Create { name } => {
+                manager.create_mod_folder(name)?;
+            }
+            Command::Config { command } => execute_config(&mut manager, command)?,
+            Command::Clear => manager.clear_configs()?,
+            Command::Run | Command::Unpack { .. } | Command::Pack { .. } | Command::Tui => {
+                unreachable!("Handled before loading the manager")
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn execute_config(manager: &mut ModManager, command: &ConfigCommand) -> anyhow::Result<()> {
+    match command {
+        ConfigCommand::Get { key: None } => print_paths(manager),
+        ConfigCommand::Get { key: Some(key) } => {
+            let paths_config = manager.configs().paths_config();
+            let path = match key {
+                ConfigKey::Game => paths_config.game_path().path(),
+                ConfigKey::Mods => paths_config.mods_path().path(),
+                ConfigKey::Group => paths_config.mods_group_path().path(),
+            };
+            println!("{}", path.display());
+        }
+        ConfigCommand::Set { key, path } => {
+            if std::fs::metadata(path).is_err() {
+                anyhow::bail!("Couldn't validate this path: {}.", path.display());
+            }
+
+            let config_path = ConfigPath::new().with_path(path.as_path());
+            let configuration_path = match key {
+                ConfigKey::Game => ConfigurationPath::Game(config_path),
+                ConfigKey::Mods => ConfigurationPath::Mods(config_path),
+                ConfigKey::Group => ConfigurationPath::Group(config_path),
+            };
+            manager.set_configuration_path(configuration_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_music(manager: &ModManager, command: &MusicCommand) -> anyhow::Result<()> {
+    match command {
+        MusicCommand::Tracks { name } => print!("{}", manager.music_tracks(name.as_deref())?),
+        MusicCommand::Diff { name } => print!("{}", manager.diff_music(name.as_deref())?),
+        MusicCommand::Export { track, file, name } => {
+            let size = manager.export_music_track(name.as_deref(), track, file)?;
+            println!("Exported {track} ({}) to {}", HumanBytes(size), file.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the vanilla check, failing when the game isn't vanilla so scripts can rely on the exit
+/// code.
+fn verify_vanilla(manager: &mut ModManager, restore: bool) -> anyhow::Result<()> {
+    let mut report = manager.verify_vanilla()?;
+    print!("{report}");
+
+    if restore && !report.is_vanilla() {
+        print_set_aside(&manager.restore_vanilla()?);
+        report = manager.verify_vanilla()?;
+        print!("{report}");
+    }
+
+    if !report.is_vanilla() {
+        anyhow::bail!("The game isn't vanilla.");
+    }
+
+    Ok(())
+}
+
+fn print_set_aside(paths: &[PathBuf]) {
+    for path in paths {
+        println!("Moved a patchwad aside to {}", path.display());
+    }
+}
+
+fn print_config_warnings(manager: &ModManager) {
+    for warning in manager.configs().warnings() {
+        eprintln!("{warning}");
+    }
+}
+
+fn print_warnings(report: &SwitchReport) {
+    for warning in report.warnings() {
+        eprintln!("{warning}");
+    }
+}
+
+fn print_mods(manager: &ModManager) {
+    let current_mod = manager.current_mod().map(CurrentMod::name);
+
+    for hm_mod in manager.all_mods().mods() {
+        let marker = if Some(hm_mod.name()) == current_mod {
+            "*"
+        } else {
+            " "
+        };
+        println!(
+            "{marker} {} ({})",
+            hm_mod.name(),
+            hm_mod.name().directory_name().display()
+        );
+    }
+}
+
+fn print_paths(manager: &ModManager) {
+    let paths_config = manager.configs().paths_config();
+
+    println!("config_dir: {}", manager.configs().config_dir());
+    println!(
+        "{}: {}",
+        GamePath::key(),
+        paths_config.game_path().path().display()
+    );
+    println!(
+        "{}: {}",
+        ModsPath::key(),
+        paths_config.mods_path().path().display()
+    );
+    println!(
+        "{}: {}",
+        ModsGroupPath::key(),
+        paths_config.mods_group_path().path().display()
+    );
+}
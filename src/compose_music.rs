@@ -0,0 +1,309 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    configs::config_dir::ConfigDir,
+    file_hash::{FileHash, HashCache},
+    functions::{available_dir, write_atomically},
+    hotline_mod::{HotlineMod, MusicTracks, MUSIC_FOLDER_NAME, VALID_MUSIC_EXTENSION},
+    wad::{
+        compose::{compose_wad, composed_len, TrackOverride, TrackSource},
+        validate::validate_wad,
+        Wad, WadError,
+    },
+};
+
+pub const COMPOSED_MUSIC_DIRECTORY_NAME: &str = "composed_music";
+pub const COMPOSED_MUSIC_VERSION: u32 = 1;
+
+/// What composing a mod's music did.
+#[derive(Debug, Clone)]
+pub struct ComposeReport {
+    music: PathBuf,
+    base: PathBuf,
+    overrides: Vec<TrackOverride>,
+    previous_music: Option<PathBuf>,
+}
+
+impl ComposeReport {
+    /// The composed music wad, now the music of the mod.
+    pub fn music(&self) -> &Path {
+        &self.music
+    }
+
+    /// The music wad the tracks without an override come from.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    pub fn overrides(&self) -> &[TrackOverride] {
+        &self.overrides
+    }
+
+    /// Where the music the mod had before was moved.
+    pub fn previous_music(&self) -> Option<&Path> {
+        self.previous_music.as_deref()
+    }
+}
+
+impl fmt::Display for ComposeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Composed {} from the tracks of {}:",
+            self.music.display(),
+            self.base.display()
+        )?;
+        for track_override in &self.overrides {
+            match track_override.source() {
+                TrackSource::Wad { path, track } => writeln!(
+                    f,
+                    "  {} from {track} in {}",
+                    track_override.track(),
+                    path.display()
+                )?,
+                TrackSource::File(path) => {
+                    writeln!(f, "  {} from {}", track_override.track(), path.display())?;
+                }
+            }
+        }
+        if let Some(previous_music) = &self.previous_music {
+            writeln!(f, "The previous music is in {}", previous_music.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a music wad from `base` with the overridden tracks, and makes it the mod's music. The
+/// music the mod had before is moved into a backup folder inside the mod.
+pub fn compose_mod_music(
+    hm_mod: &HotlineMod,
+    mod_dir: &Path,
+    base: &Path,
+    overrides: &[TrackOverride],
+) -> Result<ComposeReport, ComposeMusicError> {
+    if overrides.is_empty() {
+        return Err(ComposeMusicError::NoOverrides);
+    }
+
+    let music_name = format!(
+        "{}.{VALID_MUSIC_EXTENSION}",
+        hm_mod.name().directory_name().display()
+    );
+    let music = mod_dir.join(MUSIC_FOLDER_NAME).join(&music_name);
+    let composing = mod_dir.join(format!(".{music_name}.composing"));
+
+    compose_wad(base, overrides, &composing)?;
+
+    let previous_music = match hm_mod.music() {
+        Some(previous) => match move_into_backup(previous.path(), mod_dir) {
+            Ok(backup) => Some(backup),
+            Err(err) => {
+                let _ = fs::remove_file(&composing);
+                return Err(err);
+            }
+        },
+        None => None,
+    };
+
+    let installed = fs::create_dir_all(mod_dir.join(MUSIC_FOLDER_NAME))
+        .and_then(|()| fs::rename(&composing, &music));
+    if let Err(err) = installed {
+        if let (Some(previous), Some(backup)) = (hm_mod.music(), &previous_music) {
+            let _ = fs::rename(backup, previous.path());
+        }
+        let _ = fs::remove_file(&composing);
+        return Err(ComposeMusicError::Installing(err));
+    }
+
+    Ok(ComposeReport {
+        music,
+        base: base.to_path_buf(),
+        overrides: overrides.to_vec(),
+        previous_music,
+    })
+}
+
+/// What a music in the composed music folder was built from, to know when it's out of date.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct ComposedRecord {
+    version: u32,
+    base: FileHash,
+    tracks: Vec<ComposedTrack>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct ComposedTrack {
+    name: String,
+    sha256: FileHash,
+}
+
+/// The music a mod's `music_tracks` folder composes into, worked out without writing anything.
+///
+/// It's kept in the configuration folder with what it was built from, so switching to the mod
+/// again reuses it until the tracks or the base change.
+#[derive(Debug)]
+pub struct TrackMusic {
+    music: PathBuf,
+    base: PathBuf,
+    overrides: Vec<TrackOverride>,
+    record: ComposedRecord,
+    size: u64,
+    up_to_date: bool,
+}
+
+impl TrackMusic {
+    /// Matches the files of the mod's `music_tracks` folder with the tracks of `base`, and works
+    /// out the size of the music they compose into.
+    pub fn plan(
+        hm_mod: &HotlineMod,
+        music_tracks: &MusicTracks,
+        base: &Path,
+        config_dir: &ConfigDir,
+        cache: &mut HashCache,
+    ) -> Result<Self, ComposeMusicError> {
+        let reading_tracks =
+            |err| ComposeMusicError::ReadingTracks(err, music_tracks.path().into());
+        let base_wad = validate_wad(base)?;
+
+        let mut overrides = Vec::new();
+        let mut record = ComposedRecord {
+            version: COMPOSED_MUSIC_VERSION,
+            base: cache.hash(base).map_err(ComposeMusicError::Hashing)?,
+            tracks: Vec::new(),
+        };
+        for (name, path) in music_tracks.tracks().map_err(reading_tracks)? {
+            let name = track_name(&base_wad, name);
+            record.tracks.push(ComposedTrack {
+                name: name.clone(),
+                sha256: cache.hash(&path).map_err(ComposeMusicError::Hashing)?,
+            });
+            overrides.push(TrackOverride::new(name, TrackSource::File(path)));
+        }
+        if overrides.is_empty() {
+            return Err(ComposeMusicError::NoTracks(music_tracks.path().into()));
+        }
+
+        let music = composed_music_path(config_dir, hm_mod);
+        let up_to_date = music.is_file()
+            && fs::read_to_string(music.with_extension("toml"))
+                .ok()
+                .and_then(|contents| toml::from_str::<ComposedRecord>(&contents).ok())
+                .is_some_and(|previous| previous == record);
+        let size = composed_len(base, &overrides)?;
+
+        Ok(TrackMusic {
+            music,
+            base: base.to_path_buf(),
+            overrides,
+            record,
+            size,
+            up_to_date,
+        })
+    }
+
+    /// Where the composed music is kept, which doesn't exist yet when it isn't up to date.
+    pub fn music(&self) -> &Path {
+        &self.music
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Whether the music composed by a previous switch is still the same as the one this would
+    /// compose.
+    pub fn is_up_to_date(&self) -> bool {
+        self.up_to_date
+    }
+
+    /// Writes the composed music and what it was built from, unless it's up to date.
+    pub fn compose(&self) -> Result<&Path, ComposeMusicError> {
+        if self.up_to_date {
+            return Ok(&self.music);
+        }
+
+        if let Some(dir) = self.music.parent() {
+            fs::create_dir_all(dir).map_err(ComposeMusicError::WritingRecord)?;
+        }
+        compose_wad(&self.base, &self.overrides, &self.music)?;
+        write_atomically(
+            &self.music.with_extension("toml"),
+            toml::to_string_pretty(&self.record)?.as_bytes(),
+        )
+        .map_err(ComposeMusicError::WritingRecord)?;
+
+        Ok(&self.music)
+    }
+}
+
+/// Where the music composed from the mod's `music_tracks` folder is kept, once a switch built it.
+pub fn composed_music_path(config_dir: &ConfigDir, hm_mod: &HotlineMod) -> PathBuf {
+    config_dir.join(COMPOSED_MUSIC_DIRECTORY_NAME).join(format!(
+        "{}.{VALID_MUSIC_EXTENSION}",
+        hm_mod.name().directory_name().display()
+    ))
+}
+
+/// The name a loose track replaces in the base. A file outside any folder, like `Hotline.ogg`,
+/// replaces the only track of the base with that file name wherever it is.
+fn track_name(base: &Wad, name: String) -> String {
+    if name.contains('/') || base.find(&name).is_some() {
+        return name;
+    }
+
+    let mut same_file_name = base
+        .entries()
+        .iter()
+        .filter(|entry| entry.name().rsplit('/').next() == Some(name.as_str()));
+    match (same_file_name.next(), same_file_name.next()) {
+        (Some(entry), None) => entry.name().to_string(),
+        _ => name,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ComposeMusicError {
+    #[error("Name at least one track to take from another mod or file.")]
+    NoOverrides,
+    #[error("{0} isn't a track override, write it as TRACK=SOURCE.")]
+    InvalidOverride(Box<str>),
+    #[error("{0} isn't one of your mods or a file.")]
+    UnknownSource(Box<str>),
+    #[error("{} has no tracks.", .0.display())]
+    NoTracks(Box<Path>),
+    #[error("Error reading the music tracks in {}. Error: {0}", .1.display())]
+    ReadingTracks(io::Error, Box<Path>),
+    #[error("Error reading a track to compose the music. Error: {0}")]
+    Hashing(io::Error),
+    #[error("{0}")]
+    Wad(#[from] WadError),
+    #[error("Error trying to format what the composed music was built from. Error: {0}")]
+    SerializingRecord(#[from] toml::ser::Error),
+    #[error("Error saving the composed music. Error: {0}")]
+    WritingRecord(io::Error),
+    #[error("Error moving the previous music into {}. Error: {0}", .1.display())]
+    BackingUp(io::Error, Box<Path>),
+    #[error("Error moving the composed music into the music folder, the previous music was put back. Error: {0}")]
+    Installing(io::Error),
+}
+
+/// Moves the mod's music out of its music folder, which can only hold one.
+fn move_into_backup(music: &Path, mod_dir: &Path) -> Result<PathBuf, ComposeMusicError> {
+    let backup_dir = available_dir(&mod_dir.join(BACKUP_DIR_NAME));
+    let backup = backup_dir.join(music.file_name().unwrap_or_default());
+
+    fs::create_dir_all(&backup_dir)
+        .and_then(|()| fs::rename(music, &backup))
+        .map_err(|err| ComposeMusicError::BackingUp(err, backup_dir.as_path().into()))?;
+
+    Ok(backup)
+}
+
+const BACKUP_DIR_NAME: &str = "music_backup";
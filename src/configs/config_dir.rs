@@ -0,0 +1,106 @@
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use super::ConfigsError;
+
+pub const CONFIG_DIR_ENV_VAR: &str = "HM_MOD_MANAGER_CONFIG_DIR";
+pub const PORTABLE_MARKER_FILE_NAME: &str = "hm_mod_manager.portable";
+
+/// The folder holding the configuration file and everything else the manager stores.
+#[derive(Debug, Clone)]
+pub struct ConfigDir {
+    path: Rc<Path>,
+    source: ConfigDirSource,
+}
+
+/// Why a [`ConfigDir`] was chosen, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigDirSource {
+    /// Given with `--config-dir`.
+    Argument,
+    /// Given with the [`CONFIG_DIR_ENV_VAR`] environment variable.
+    Environment,
+    /// A [`PORTABLE_MARKER_FILE_NAME`] file sits next to the executable.
+    Portable,
+    /// The platform's configuration folder, like `~/.config` or `%APPDATA%`.
+    Platform,
+}
+
+impl ConfigDir {
+    pub fn resolve(argument: Option<&Path>) -> Result<Self, ConfigsError> {
+        if let Some(path) = argument {
+            return Ok(Self::new(path, ConfigDirSource::Argument));
+        }
+
+        if let Some(path) = env::var_os(CONFIG_DIR_ENV_VAR).filter(|path| !path.is_empty()) {
+            return Ok(Self::new(PathBuf::from(path), ConfigDirSource::Environment));
+        }
+
+        if let Some(executable_dir) =
+            executable_dir().filter(|dir| dir.join(PORTABLE_MARKER_FILE_NAME).is_file())
+        {
+            return Ok(Self::new(executable_dir, ConfigDirSource::Portable));
+        }
+
+        dirs::config_dir()
+            .map(|dir| Self::new(dir.join(APPLICATION_DIR_NAME), ConfigDirSource::Platform))
+            .ok_or(ConfigsError::ConfigDirNotFound)
+    }
+
+    pub fn new(path: impl Into<Rc<Path>>, source: ConfigDirSource) -> Self {
+        ConfigDir {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn source(&self) -> ConfigDirSource {
+        self.source
+    }
+
+    pub fn join(&self, file_name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(file_name)
+    }
+
+    /// Folders where older versions kept their configuration, relative to where they ran.
+    pub fn legacy_dirs() -> Vec<PathBuf> {
+        let mut dirs = Vec::from_iter(env::current_dir());
+        if let Some(executable_dir) = executable_dir().filter(|dir| !dirs.contains(dir)) {
+            dirs.push(executable_dir);
+        }
+        dirs
+    }
+}
+
+impl fmt::Display for ConfigDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.path.display(), self.source)
+    }
+}
+
+impl fmt::Display for ConfigDirSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ConfigDirSource::Argument => "from --config-dir",
+            ConfigDirSource::Environment => "from HM_MOD_MANAGER_CONFIG_DIR",
+            ConfigDirSource::Portable => "portable mode",
+            ConfigDirSource::Platform => "platform default",
+        };
+        write!(f, "{message}")
+    }
+}
+
+fn executable_dir() -> Option<PathBuf> {
+    env::current_exe()
+        .ok()
+        .and_then(|executable| executable.parent().map(Path::to_path_buf))
+}
+
+const APPLICATION_DIR_NAME: &str = "hotline_miami_mod_manager";
@@ -0,0 +1,125 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    config_dir::ConfigDir,
+    current_mod_config::CurrentMod,
+    paths_config::{GamePath, ModsGroupPath, ModsPath, PathsConfig, ProgramPath},
+    settings::ManagerSettings,
+    ConfigsError,
+};
+use crate::{functions::write_atomically, hotline_mod::HotlineModName};
+
+pub const CONFIG_FILE_NAME: &str = "hm_mod_manager.toml";
+pub const CONFIG_FILE_VERSION: u32 = 1;
+
+/// The on-disk layout of [`CONFIG_FILE_NAME`].
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConfigFile {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paths: Option<PathsSection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_mod: Option<CurrentModSection>,
+    #[serde(default)]
+    pub settings: ManagerSettings,
+    /// When each mod was last switched to, in seconds since the Unix epoch, by folder name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_used: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PathsSection {
+    pub game_path: PathBuf,
+    pub mods_path: PathBuf,
+    pub mods_group_path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurrentModSection {
+    pub name: String,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        ConfigFile {
+            version: CONFIG_FILE_VERSION,
+            paths: None,
+            current_mod: None,
+            settings: ManagerSettings::default(),
+            last_used: BTreeMap::new(),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Returns `None` when the file doesn't exist yet.
+    pub fn read(config_dir: &ConfigDir) -> Result<Option<Self>, ConfigsError> {
+        let contents = match fs::read_to_string(config_dir.join(CONFIG_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(ConfigsError::FileLoadingError(err)),
+        };
+
+        let file = toml::from_str::<ConfigFile>(&contents)?;
+
+        if file.version > CONFIG_FILE_VERSION {
+            return Err(ConfigsError::UnsupportedVersion(file.version));
+        }
+
+        Ok(Some(file))
+    }
+
+    pub fn write(&self, config_dir: &ConfigDir) -> Result<(), ConfigsError> {
+        let contents = toml::to_string_pretty(self)?;
+
+        fs::create_dir_all(config_dir.path())
+            .and_then(|()| {
+                write_atomically(&config_dir.join(CONFIG_FILE_NAME), contents.as_bytes())
+            })
+            .map_err(ConfigsError::FileWritingError)
+    }
+
+    pub fn remove(config_dir: &ConfigDir) -> Result<(), ConfigsError> {
+        fs::remove_file(config_dir.join(CONFIG_FILE_NAME)).map_err(ConfigsError::FileClearingError)
+    }
+}
+
+impl From<&PathsConfig> for PathsSection {
+    fn from(paths_config: &PathsConfig) -> Self {
+        PathsSection {
+            game_path: paths_config.game_path().path().to_path_buf(),
+            mods_path: paths_config.mods_path().path().to_path_buf(),
+            mods_group_path: paths_config.mods_group_path().path().to_path_buf(),
+        }
+    }
+}
+
+impl From<PathsSection> for PathsConfig {
+    fn from(section: PathsSection) -> Self {
+        PathsConfig::new(
+            GamePath::new(section.game_path),
+            ModsPath::new(section.mods_path),
+            ModsGroupPath::new(section.mods_group_path),
+        )
+    }
+}
+
+impl From<&CurrentMod> for CurrentModSection {
+    fn from(current_mod: &CurrentMod) -> Self {
+        CurrentModSection {
+            name: current_mod
+                .name()
+                .directory_name()
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+}
+
+impl From<CurrentModSection> for CurrentMod {
+    fn from(section: CurrentModSection) -> Self {
+        CurrentMod::from_mod(HotlineModName::from_directory(section.name))
+    }
+}
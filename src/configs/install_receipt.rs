@@ -0,0 +1,272 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    configs::paths_config::{ModsPath, ProgramPath},
+    file_hash::{FileHash, HashCache},
+    functions::write_atomically,
+    hotline_mod::HotlineMod,
+    replace_mod::install_strategy::InstallMethod,
+};
+
+use super::config_dir::ConfigDir;
+
+pub const INSTALL_RECEIPT_FILE_NAME: &str = "install_receipt.toml";
+pub const INSTALL_RECEIPT_VERSION: u32 = 1;
+
+/// Every file the manager placed in the game folders during the last switch, with its hash.
+///
+/// Only the files listed here are removed on the next switch, anything else in the mods folder
+/// belongs to the user.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InstallReceipt {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mod_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    music: Option<ReceiptEntry>,
+    #[serde(default)]
+    mods: Vec<ReceiptEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReceiptEntry {
+    path: PathBuf,
+    sha256: FileHash,
+    #[serde(default)]
+    method: InstallMethod,
+}
+
+/// How the patchwads in the mods folder compare to the install receipt.
+#[derive(Debug, Default)]
+pub struct ModsFolderSurvey {
+    managed: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    unmanaged: Vec<PathBuf>,
+}
+
+impl Default for InstallReceipt {
+    fn default() -> Self {
+        InstallReceipt {
+            version: INSTALL_RECEIPT_VERSION,
+            mod_name: None,
+            music: None,
+            mods: Vec::new(),
+        }
+    }
+}
+
+impl InstallReceipt {
+    /// Hashes the files a switch just placed, noting how each one was installed.
+    pub fn record(
+        hm_mod: &HotlineMod,
+        music: Option<(&Path, InstallMethod)>,
+        mods: &[(PathBuf, InstallMethod)],
+        cache: &mut HashCache,
+    ) -> io::Result<Self> {
+        Ok(InstallReceipt {
+            mod_name: Some(
+                hm_mod
+                    .name()
+                    .directory_name()
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            music: music
+                .map(|(path, method)| ReceiptEntry::new(path, method, cache))
+                .transpose()?,
+            mods: mods
+                .iter()
+                .map(|(path, method)| ReceiptEntry::new(path, *method, cache))
+                .collect::<io::Result<_>>()?,
+            ..InstallReceipt::default()
+        })
+    }
+
+    /// Builds a receipt for a mods folder switched before receipts existed, claiming the files
+    /// that are identical to the ones of the mod in use.
+    pub fn adopt(mods_path: &ModsPath, current_mod: &HotlineMod) -> Self {
+        let mods = current_mod
+            .mods()
+            .mods()
+            .iter()
+            .filter_map(|source| {
+                let installed = mods_path.path().join(source.file_name()?);
+                let sha256 = FileHash::of_path(&installed).ok()?;
+                (FileHash::of_path(source).ok()? == sha256).then_some(ReceiptEntry {
+                    path: installed,
+                    sha256,
+                    method: InstallMethod::Copy,
+                })
+            })
+            .collect();
+
+        InstallReceipt {
+            mod_name: Some(
+                current_mod
+                    .name()
+                    .directory_name()
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+            mods,
+            ..InstallReceipt::default()
+        }
+    }
+
+    /// Returns `None` when no switch was recorded yet.
+    pub fn read(config_dir: &ConfigDir) -> Result<Option<Self>, InstallReceiptError> {
+        let contents = match fs::read_to_string(config_dir.join(INSTALL_RECEIPT_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(InstallReceiptError::FileLoadingError(err)),
+        };
+
+        let receipt = toml::from_str::<InstallReceipt>(&contents)?;
+
+        if receipt.version > INSTALL_RECEIPT_VERSION {
+            return Err(InstallReceiptError::UnsupportedVersion(receipt.version));
+        }
+
+        Ok(Some(receipt))
+    }
+
+    pub fn write(&self, config_dir: &ConfigDir) -> Result<(), InstallReceiptError> {
+        let contents = toml::to_string_pretty(self)?;
+
+        fs::create_dir_all(config_dir.path())
+            .and_then(|()| {
+                write_atomically(
+                    &config_dir.join(INSTALL_RECEIPT_FILE_NAME),
+                    contents.as_bytes(),
+                )
+            })
+            .map_err(InstallReceiptError::FileWritingError)
+    }
+
+    pub fn mod_name(&self) -> Option<&str> {
+        self.mod_name.as_deref()
+    }
+
+    pub fn music(&self) -> Option<&ReceiptEntry> {
+        self.music.as_ref()
+    }
+
+    pub fn mods(&self) -> &[ReceiptEntry] {
+        &self.mods
+    }
+
+    /// How the file at `path` was installed, or a copy when the receipt doesn't list it.
+    pub fn method_of(&self, path: &Path) -> InstallMethod {
+        self.music
+            .iter()
+            .chain(&self.mods)
+            .find(|entry| entry.path == path)
+            .map_or(InstallMethod::Copy, |entry| entry.method)
+    }
+
+    /// Sorts the receipt's files that are still installed into managed and modified, and the
+    /// installed patchwads the receipt doesn't know about into unmanaged.
+    pub fn survey(&self, installed_patchwads: Vec<PathBuf>) -> io::Result<ModsFolderSurvey> {
+        let mut survey = ModsFolderSurvey::default();
+
+        for entry in &self.mods {
+            // A link the manager made points at the mod's file, so whatever the file holds now
+            // it's still the manager's, and removing the link never loses anything.
+            if entry.method == InstallMethod::Symlink {
+                match fs::symlink_metadata(&entry.path) {
+                    Ok(metadata) if metadata.file_type().is_symlink() => {
+                        survey.managed.push(entry.path.clone());
+                    }
+                    Ok(_) => survey.modified.push(entry.path.clone()),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err),
+                }
+                continue;
+            }
+
+            match FileHash::of_path(&entry.path) {
+                Ok(sha256) if sha256 == entry.sha256 => survey.managed.push(entry.path.clone()),
+                Ok(_) => survey.modified.push(entry.path.clone()),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        survey.unmanaged = installed_patchwads
+            .into_iter()
+            .filter(|path| !self.mods.iter().any(|entry| entry.path == *path))
+            .collect();
+
+        Ok(survey)
+    }
+}
+
+impl ReceiptEntry {
+    fn new(path: &Path, method: InstallMethod, cache: &mut HashCache) -> io::Result<Self> {
+        Ok(ReceiptEntry {
+            path: path.to_path_buf(),
+            sha256: cache.hash(path)?,
+            method,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn sha256(&self) -> &FileHash {
+        &self.sha256
+    }
+
+    pub fn method(&self) -> InstallMethod {
+        self.method
+    }
+}
+
+impl ModsFolderSurvey {
+    /// Files the manager placed and that are unchanged, safe to remove.
+    pub fn managed(&self) -> &[PathBuf] {
+        &self.managed
+    }
+
+    /// Files the manager placed but that were changed since.
+    pub fn modified(&self) -> &[PathBuf] {
+        &self.modified
+    }
+
+    /// Patchwads the manager didn't place.
+    pub fn unmanaged(&self) -> &[PathBuf] {
+        &self.unmanaged
+    }
+
+    /// Whether a switch may overwrite this path.
+    pub fn can_replace(&self, path: &Path) -> bool {
+        fs::symlink_metadata(path).is_err() || self.managed.iter().any(|managed| managed == path)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum InstallReceiptError {
+    #[error("File {INSTALL_RECEIPT_FILE_NAME} failed to load. Error: {0}")]
+    FileLoadingError(io::Error),
+    #[error("Content found in {INSTALL_RECEIPT_FILE_NAME} could not be read. Error: {0}")]
+    InvalidFileContent(#[from] toml::de::Error),
+    #[error(
+        "{INSTALL_RECEIPT_FILE_NAME} was written by a newer version of the program (version {0})."
+    )]
+    UnsupportedVersion(u32),
+    #[error("Error trying to format the install receipt. Error: {0}")]
+    SerializingError(#[from] toml::ser::Error),
+    #[error(
+        "Error trying to write the install receipt to {INSTALL_RECEIPT_FILE_NAME}. Error: {0}"
+    )]
+    FileWritingError(io::Error),
+    #[error("Error checking the files in your mods folder. Error: {0}")]
+    HashingError(#[from] io::Error),
+}
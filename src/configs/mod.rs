@@ -1,41 +1,78 @@
+pub mod config_dir;
+pub mod config_file;
 pub mod current_mod_config;
+pub mod install_receipt;
+pub mod music_vault;
 pub mod paths_config;
+pub mod settings;
 
+use std::{
+    collections::BTreeMap,
+    io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
 
-use crate::hotline_mod::HotlineModName;
+use crate::{hotline_mod::HotlineModName, prompter::Prompter};
 
 use self::{
-    current_mod_config::{CurrentMod, CurrentModError},
-    paths_config::PathsConfig,
+    config_dir::ConfigDir,
+    config_file::{ConfigFile, CONFIG_FILE_NAME},
+    current_mod_config::CurrentMod,
+    paths_config::{ConfigurationPath, PathsConfig, PathsConfigError, WithPath},
+    settings::ManagerSettings,
 };
 
 #[derive(Debug)]
 pub struct Configs {
+    config_dir: ConfigDir,
     paths_config: PathsConfig,
     current_mod: Option<CurrentMod>,
+    settings: ManagerSettings,
+    last_used: BTreeMap<String, u64>,
+    warnings: Vec<ConfigsError>,
 }
 
 impl Configs {
-    pub fn build() -> anyhow::Result<Self> {
-        let paths_config = PathsConfig::build()?;
-        let current_mod = CurrentMod::build()
-            .inspect_err(Self::on_current_mod_error)
-            .ok();
+    pub fn build(config_dir: ConfigDir, prompter: &mut dyn Prompter) -> Result<Self, ConfigsError> {
+        let mut warnings = Vec::new();
+        let mut file = Self::read_file(&config_dir, &mut warnings)?.unwrap_or_default();
 
-        Ok(Configs {
-            paths_config,
-            current_mod,
-        })
+        if file.paths.is_none() {
+            let paths_config = PathsConfig::request_from_user(prompter)?;
+            file.paths = Some((&paths_config).into());
+            file.write(&config_dir)?;
+        }
+
+        Self::from_file(config_dir, file, warnings)
     }
 
-    pub fn clear(&self) -> anyhow::Result<()> {
-        self.paths_config.clear()?;
+    pub fn load(config_dir: ConfigDir) -> Result<Self, ConfigsError> {
+        let mut warnings = Vec::new();
+        let file = Self::read_file(&config_dir, &mut warnings)?.unwrap_or_default();
+
+        Self::from_file(config_dir, file, warnings)
+    }
 
-        if let Some(current_mod) = &self.current_mod {
-            current_mod.clear()?;
+    pub fn save(&self) -> Result<(), ConfigsError> {
+        ConfigFile {
+            paths: Some((&self.paths_config).into()),
+            current_mod: self.current_mod.as_ref().map(Into::into),
+            settings: self.settings.clone(),
+            last_used: self.last_used.clone(),
+            ..ConfigFile::default()
         }
+        .write(&self.config_dir)
+    }
+
+    pub fn clear(&self) -> Result<(), ConfigsError> {
+        ConfigFile::remove(&self.config_dir)
+    }
 
-        Ok(())
+    pub fn config_dir(&self) -> &ConfigDir {
+        &self.config_dir
     }
 
     pub fn paths_config(&self) -> &PathsConfig {
@@ -50,26 +87,156 @@ impl Configs {
         self.current_mod.as_ref()
     }
 
-    pub fn set_paths_config(
-        &mut self,
-        paths_config: PathsConfig,
-    ) -> Result<(), paths_config::PathsConfigError> {
+    pub fn settings(&self) -> &ManagerSettings {
+        &self.settings
+    }
+
+    /// What went wrong while loading without stopping it, like an unreadable legacy file.
+    pub fn warnings(&self) -> &[ConfigsError] {
+        &self.warnings
+    }
+
+    /// When the mod was last switched to, if ever.
+    pub fn last_used(&self, name: &HotlineModName) -> Option<SystemTime> {
+        self.last_used
+            .get(name.directory_name().to_string_lossy().as_ref())
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(*secs))
+    }
+
+    pub fn set_paths_config(&mut self, paths_config: PathsConfig) -> Result<(), ConfigsError> {
         self.paths_config = paths_config;
-        self.paths_config.save()
+        self.save()
     }
 
-    pub fn set_current_mod(&mut self, current_mod: HotlineModName) -> Result<(), CurrentModError> {
-        let current_mod = CurrentMod::from_mod(current_mod);
-        current_mod.save()?;
-        self.current_mod = Some(current_mod);
-        Ok(())
+    /// Changes one of the paths. Keeps the previous path in memory when saving fails, so it still
+    /// matches the file.
+    pub fn set_path(
+        &mut self,
+        configuration_path: ConfigurationPath<WithPath>,
+    ) -> Result<(), ConfigsError> {
+        let previous = self.paths_config.clone();
+
+        match configuration_path {
+            ConfigurationPath::Game(path) => self.paths_config.set_game_path(path.into()),
+            ConfigurationPath::Mods(path) => self.paths_config.set_mods_path(path.into()),
+            ConfigurationPath::Group(path) => self.paths_config.set_mods_group_path(path.into()),
+        }
+
+        self.save().inspect_err(|_| self.paths_config = previous)
     }
 
-    fn on_current_mod_error(err: &CurrentModError) {
-        if let CurrentModError::IoError(error) = err {
-            println!(
-                "Something wrong happened while trying to read the current mod: {error}"
-            );
+    /// Also records that the mod was used now. Keeps the previous state in memory when saving
+    /// fails, so it still matches the file.
+    pub fn set_current_mod(&mut self, current_mod: HotlineModName) -> Result<(), ConfigsError> {
+        let previous_last_used = self.last_used.clone();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.last_used.insert(
+            current_mod.directory_name().to_string_lossy().into_owned(),
+            now,
+        );
+        let previous = self.current_mod.replace(CurrentMod::from_mod(current_mod));
+
+        self.save().inspect_err(|_| {
+            self.current_mod = previous;
+            self.last_used = previous_last_used;
+        })
+    }
+
+    fn from_file(
+        config_dir: ConfigDir,
+        file: ConfigFile,
+        warnings: Vec<ConfigsError>,
+    ) -> Result<Self, ConfigsError> {
+        let paths_config = file.paths.ok_or(ConfigsError::PathsNotConfigured)?.into();
+
+        Ok(Configs {
+            config_dir,
+            paths_config,
+            current_mod: file.current_mod.map(Into::into),
+            settings: file.settings,
+            last_used: file.last_used,
+            warnings,
+        })
+    }
+
+    /// Reads the configuration file, migrating the pre-TOML files into it when it doesn't
+    /// exist yet.
+    fn read_file(
+        config_dir: &ConfigDir,
+        warnings: &mut Vec<ConfigsError>,
+    ) -> Result<Option<ConfigFile>, ConfigsError> {
+        if let Some(file) = ConfigFile::read(config_dir)? {
+            return Ok(Some(file));
+        }
+
+        for legacy_dir in ConfigDir::legacy_dirs() {
+            if let Some(file) = Self::migrate_legacy_files(config_dir, &legacy_dir, warnings)? {
+                return Ok(Some(file));
+            }
         }
+
+        Ok(None)
     }
+
+    fn migrate_legacy_files(
+        config_dir: &ConfigDir,
+        legacy_dir: &Path,
+        warnings: &mut Vec<ConfigsError>,
+    ) -> Result<Option<ConfigFile>, ConfigsError> {
+        let paths_config = PathsConfig::from_legacy_file(legacy_dir)?;
+        let current_mod = CurrentMod::from_legacy_file(legacy_dir)
+            .map_err(|err| warnings.push(ConfigsError::LegacyCurrentModError(err)))
+            .ok()
+            .flatten();
+
+        if paths_config.is_none() && current_mod.is_none() {
+            return Ok(None);
+        }
+
+        let file = ConfigFile {
+            paths: paths_config.as_ref().map(Into::into),
+            current_mod: current_mod.as_ref().map(Into::into),
+            ..ConfigFile::default()
+        };
+        file.write(config_dir)?;
+
+        PathsConfig::remove_legacy_file(legacy_dir)
+            .map_err(ConfigsError::LegacyFileRemovalError)?;
+        CurrentMod::remove_legacy_file(legacy_dir).map_err(ConfigsError::LegacyFileRemovalError)?;
+
+        Ok(Some(file))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigsError {
+    #[error(
+        "Couldn't find a folder to keep the configuration in. Use --config-dir to choose one."
+    )]
+    ConfigDirNotFound,
+    #[error("File {CONFIG_FILE_NAME} failed to load. Error: {0}")]
+    FileLoadingError(io::Error),
+    #[error("Content found in {CONFIG_FILE_NAME} could not be read. Error: {0}")]
+    InvalidFileContent(#[from] toml::de::Error),
+    #[error("{CONFIG_FILE_NAME} was written by a newer version of the program (version {0}).")]
+    UnsupportedVersion(u32),
+    #[error("Error trying to format the configuration. Error: {0}")]
+    SerializingError(#[from] toml::ser::Error),
+    #[error("Error trying to write the configuration to {CONFIG_FILE_NAME}. Error: {0}")]
+    FileWritingError(io::Error),
+    #[error("Something went wrong when deleting the file {CONFIG_FILE_NAME}. Error: {0}")]
+    FileClearingError(io::Error),
+    #[error(
+        "Your paths are not configured yet. Run the program without arguments to set them up."
+    )]
+    PathsNotConfigured,
+    #[error("Something went wrong when deleting an old configuration file. Error: {0}")]
+    LegacyFileRemovalError(io::Error),
+    #[error("Something wrong happened while trying to read the current mod: {0}")]
+    LegacyCurrentModError(io::Error),
+    #[error("{0}")]
+    PathsConfig(#[from] PathsConfigError),
 }
@@ -0,0 +1,152 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    file_hash::{FileHash, HashCache},
+    functions::write_atomically,
+    replace_mod::replace_music::MUSIC_FILE_NAME,
+};
+
+use super::config_dir::ConfigDir;
+
+pub const MUSIC_VAULT_FILE_NAME: &str = "music_vault.toml";
+pub const MUSIC_VAULT_DIRECTORY_NAME: &str = "music_vault";
+pub const MUSIC_VAULT_VERSION: u32 = 1;
+
+/// A copy of the original game music, taken before any switch replaced it.
+#[derive(Debug, Clone)]
+pub struct MusicVault {
+    music: PathBuf,
+    record: VaultRecord,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct VaultRecord {
+    version: u32,
+    sha256: FileHash,
+    captured_from: PathBuf,
+}
+
+impl MusicVault {
+    /// Whether the original music was vaulted before, even if the copy is gone or damaged now.
+    pub fn exists(config_dir: &ConfigDir) -> bool {
+        config_dir.join(MUSIC_VAULT_FILE_NAME).exists()
+    }
+
+    /// Returns `None` when nothing was vaulted yet, and an error when the vaulted music no longer
+    /// matches the hash it was vaulted with.
+    pub fn open(
+        config_dir: &ConfigDir,
+        cache: &mut HashCache,
+    ) -> Result<Option<Self>, MusicVaultError> {
+        let contents = match fs::read_to_string(config_dir.join(MUSIC_VAULT_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(MusicVaultError::FileLoadingError(err)),
+        };
+
+        let record = toml::from_str::<VaultRecord>(&contents)?;
+
+        if record.version > MUSIC_VAULT_VERSION {
+            return Err(MusicVaultError::UnsupportedVersion(record.version));
+        }
+
+        let music = vault_music_path(config_dir);
+        let sha256 = cache.hash(&music).map_err(MusicVaultError::ReadingVault)?;
+        if sha256 != record.sha256 {
+            return Err(MusicVaultError::Damaged(music));
+        }
+
+        Ok(Some(MusicVault { music, record }))
+    }
+
+    /// Copies the original music into the vault, checking the copy against the original before
+    /// recording it.
+    pub fn capture(
+        config_dir: &ConfigDir,
+        original: &Path,
+        cache: &mut HashCache,
+    ) -> Result<Self, MusicVaultError> {
+        let music = vault_music_path(config_dir);
+        let temp_music = music.with_extension("wad.tmp");
+
+        let copied = fs::create_dir_all(config_dir.join(MUSIC_VAULT_DIRECTORY_NAME))
+            .and_then(|()| fs::copy(original, &temp_music))
+            .and_then(|_| File::open(&temp_music)?.sync_all())
+            .and_then(|()| fs::rename(&temp_music, &music));
+        if let Err(err) = copied {
+            let _ = fs::remove_file(&temp_music);
+            return Err(MusicVaultError::Copying(err));
+        }
+
+        let sha256 = cache
+            .hash(original)
+            .map_err(MusicVaultError::ReadingVault)?;
+        if cache.hash(&music).map_err(MusicVaultError::ReadingVault)? != sha256 {
+            let _ = fs::remove_file(&music);
+            return Err(MusicVaultError::Copying(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the copy doesn't match the original",
+            )));
+        }
+
+        let record = VaultRecord {
+            version: MUSIC_VAULT_VERSION,
+            sha256,
+            captured_from: original.to_path_buf(),
+        };
+        let contents = toml::to_string_pretty(&record)?;
+        write_atomically(&config_dir.join(MUSIC_VAULT_FILE_NAME), contents.as_bytes())
+            .map_err(MusicVaultError::FileWritingError)?;
+
+        Ok(MusicVault { music, record })
+    }
+
+    /// The vaulted copy of the music.
+    pub fn music(&self) -> &Path {
+        &self.music
+    }
+
+    pub fn sha256(&self) -> &FileHash {
+        &self.record.sha256
+    }
+
+    /// Where the music was copied from.
+    pub fn captured_from(&self) -> &Path {
+        &self.record.captured_from
+    }
+}
+
+fn vault_music_path(config_dir: &ConfigDir) -> PathBuf {
+    config_dir
+        .join(MUSIC_VAULT_DIRECTORY_NAME)
+        .join(MUSIC_FILE_NAME)
+}
+
+#[derive(Error, Debug)]
+pub enum MusicVaultError {
+    #[error("File {MUSIC_VAULT_FILE_NAME} failed to load. Error: {0}")]
+    FileLoadingError(io::Error),
+    #[error("Content found in {MUSIC_VAULT_FILE_NAME} could not be read. Error: {0}")]
+    InvalidFileContent(#[from] toml::de::Error),
+    #[error(
+        "{MUSIC_VAULT_FILE_NAME} was written by a newer version of the program (version {0})."
+    )]
+    UnsupportedVersion(u32),
+    #[error("Error trying to format the music vault record. Error: {0}")]
+    SerializingError(#[from] toml::ser::Error),
+    #[error("Error trying to write the music vault record to {MUSIC_VAULT_FILE_NAME}. Error: {0}")]
+    FileWritingError(io::Error),
+    #[error("Error copying the original game music into the vault. Error: {0}")]
+    Copying(io::Error),
+    #[error("Error reading the original game music. Error: {0}")]
+    ReadingVault(io::Error),
+    #[error("The copy of the original game music at {} was changed after it was vaulted. To vault it again, delete {MUSIC_VAULT_FILE_NAME} and the {MUSIC_VAULT_DIRECTORY_NAME} folder from the configuration folder, and verify the game files before running the program.", .0.display())]
+    Damaged(PathBuf),
+}
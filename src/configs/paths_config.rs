@@ -1,17 +1,19 @@
 use std::{
     collections::HashMap,
     fmt::Display,
-    fs::{self, metadata, File},
-    io::{self, Write},
+    fs::{self, metadata},
+    io::{self, ErrorKind},
     path::{Path, PathBuf},
     rc::Rc,
 };
 
 use thiserror::Error;
 
-use crate::functions::get_user_input;
+use inquire::InquireError;
 
-#[derive(Debug)]
+use crate::{functions::get_user_input, prompter::Prompter};
+
+#[derive(Debug, Clone)]
 pub struct PathsConfig {
     game: GamePath,
     mods: ModsPath,
@@ -31,9 +33,33 @@ impl Display for PathsConfig {
 }
 
 impl PathsConfig {
-    pub fn build() -> Result<Self, PathsConfigError> {
-        let Ok(file) = Self::get_path_configs_file() else {
-            return Self::create_path_configs_file();
+    pub fn new(game: GamePath, mods: ModsPath, mods_group: ModsGroupPath) -> Self {
+        PathsConfig {
+            game,
+            mods,
+            mods_group,
+        }
+    }
+
+    /// Asks the user for every path, as done the first time the program runs.
+    pub fn request_from_user(prompter: &mut dyn Prompter) -> Result<Self, PathsConfigError> {
+        let game_path = Self::request_path_from_user::<GamePath>(prompter)?;
+        let mods_path = Self::request_path_from_user::<ModsPath>(prompter)?;
+        let mods_group_path = Self::request_path_from_user::<ModsGroupPath>(prompter)?;
+
+        Ok(PathsConfig {
+            game: game_path,
+            mods: mods_path,
+            mods_group: mods_group_path,
+        })
+    }
+
+    /// Reads the `key:value` file used before the TOML configuration, if it still exists.
+    pub fn from_legacy_file(dir: &Path) -> Result<Option<Self>, PathsConfigError> {
+        let file = match fs::read_to_string(dir.join(LEGACY_PATH_CONFIGS_FILE_NAME)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(PathsConfigError::FileLoadingError(err)),
         };
 
         let mut entries = file
@@ -58,25 +84,18 @@ impl PathsConfig {
             .map(ModsGroupPath::new)
             .ok_or(PathsConfigError::ModsGroupPathNotFound)?;
 
-        Ok(PathsConfig {
+        Ok(Some(PathsConfig {
             game: game_path,
             mods: mods_path,
             mods_group: mods_group_path,
-        })
-    }
-
-    pub fn clear(&self) -> Result<(), PathsConfigError> {
-        fs::remove_file(PATH_CONFIGS_FILE_NAME).map_err(PathsConfigError::FileClearingError)
+        }))
     }
 
-    pub fn save(&self) -> Result<(), PathsConfigError> {
-        let entries = [
-            self.game.as_file_entry(),
-            self.mods.as_file_entry(),
-            self.mods_group.as_file_entry(),
-        ];
-
-        Self::flush_entries(&entries)
+    pub fn remove_legacy_file(dir: &Path) -> io::Result<()> {
+        match fs::remove_file(dir.join(LEGACY_PATH_CONFIGS_FILE_NAME)) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
     }
 
     pub fn game_path(&self) -> &GamePath {
@@ -116,87 +135,35 @@ impl PathsConfig {
         }
     }
 
-    fn get_path_configs_file() -> Result<String, PathsConfigError> {
-        let configs = fs::read_to_string(PATH_CONFIGS_FILE_NAME)
-            .map_err(PathsConfigError::FileLoadingError)?;
-
-        if configs.lines().collect::<Vec<_>>().len() != 3 {
-            return Err(PathsConfigError::InvalidFileContent);
-        }
-
-        Ok(configs)
-    }
-
-    fn create_path_configs_file() -> Result<Self, PathsConfigError> {
-        let mut file =
-            File::create(PATH_CONFIGS_FILE_NAME).map_err(PathsConfigError::FileLoadingError)?;
-        let game_path = Self::request_path_from_user::<GamePath>();
-        let mods_path = Self::request_path_from_user::<ModsPath>();
-        let mods_group_path = Self::request_path_from_user::<ModsGroupPath>();
-        let entries = [
-            game_path.as_file_entry(),
-            mods_path.as_file_entry(),
-            mods_group_path.as_file_entry(),
-        ];
-
-        _ = file
-            .write(Self::format_paths_for_file(&entries).as_bytes())
-            .map_err(PathsConfigError::FileWritingError)?;
-
-        Ok(PathsConfig {
-            game: game_path,
-            mods: mods_path,
-            mods_group: mods_group_path,
-        })
-    }
-
-    fn flush_entries(entries: &[PathFileEntry]) -> Result<(), PathsConfigError> {
-        _ = File::open(PATH_CONFIGS_FILE_NAME)
-            .map_err(PathsConfigError::FileLoadingError)?
-            .write(Self::format_paths_for_file(entries).as_bytes())
-            .map_err(PathsConfigError::FileWritingError)?;
-
-        Ok(())
-    }
-
-    fn request_path_from_user<P: ProgramPath>() -> P {
-        let path = PathBuf::from(get_user_input(P::prompt()));
+    fn request_path_from_user<P: ProgramPath>(
+        prompter: &mut dyn Prompter,
+    ) -> Result<P, PathsConfigError> {
+        let path = PathBuf::from(
+            get_user_input(prompter, P::prompt()).map_err(PathsConfigError::PromptError)?,
+        );
         if metadata(&path).is_ok() {
-            P::new(path)
+            Ok(P::new(path))
         } else {
-            println!(
-                "Could not validate your {} path, please write it again.",
-                P::name()
-            );
-            Self::request_path_from_user()
-        }
-    }
-
-    fn format_paths_for_file(entries: &[PathFileEntry]) -> String {
-        let mut buffer = String::new();
-        for entry in entries {
-            buffer += &entry.format();
+            prompter.warn(&PathsConfigError::InvalidPath(P::name()));
+            Self::request_path_from_user(prompter)
         }
-        buffer
     }
 }
 
 #[derive(Error, Debug)]
 pub enum PathsConfigError {
-    #[error("File containing path configuration failed to load.")]
+    #[error("File {LEGACY_PATH_CONFIGS_FILE_NAME} failed to load. Error: {0}")]
     FileLoadingError(io::Error),
-    #[error("Content found in path configuration file could not be read.")]
-    InvalidFileContent,
-    #[error("Error trying to write paths to file.")]
-    FileWritingError(io::Error),
-    #[error("Game path not found in configuration file.")]
+    #[error("Game path not found in {LEGACY_PATH_CONFIGS_FILE_NAME}.")]
     GamePathNotFound,
-    #[error("Mods path not found in configuration file.")]
+    #[error("Mods path not found in {LEGACY_PATH_CONFIGS_FILE_NAME}.")]
     ModsPathNotFound,
-    #[error("Mods group path not found in configuration file.")]
+    #[error("Mods group path not found in {LEGACY_PATH_CONFIGS_FILE_NAME}.")]
     ModsGroupPathNotFound,
-    #[error("Something went wrong when deleting the file {PATH_CONFIGS_FILE_NAME}. Error: {0}")]
-    FileClearingError(io::Error),
+    #[error("Could not validate your {0} path, please write it again.")]
+    InvalidPath(&'static str),
+    #[error("The paths were not configured. Error: {0}")]
+    PromptError(InquireError),
 }
 
 pub trait ProgramPath {
@@ -205,23 +172,6 @@ pub trait ProgramPath {
     fn key() -> &'static str;
     fn name() -> &'static str;
     fn prompt() -> &'static str;
-    fn as_file_entry(&self) -> PathFileEntry<'_> {
-        PathFileEntry {
-            key: Self::key(),
-            path: self.path(),
-        }
-    }
-}
-#[derive(Debug)]
-pub struct PathFileEntry<'a> {
-    key: &'static str,
-    path: &'a Path,
-}
-
-impl PathFileEntry<'_> {
-    fn format(&self) -> String {
-        format!("{}:{}\n", self.key, self.path.display())
-    }
 }
 
 #[derive(Debug, Clone)]
@@ -420,4 +370,4 @@ impl Display for ConfigurationPath<WithPath> {
     }
 }
 
-const PATH_CONFIGS_FILE_NAME: &str = "hm_mod_manager_path_configs.conf";
+const LEGACY_PATH_CONFIGS_FILE_NAME: &str = "hm_mod_manager_path_configs.conf";
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{replace_mod::install_strategy::InstallMethod, select_mod::ModSort};
+
+/// Preferences for the manager itself, stored in the `[settings]` section.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ManagerSettings {
+    exit_delay_secs: u64,
+    install_strategy: InstallMethod,
+    mod_sort: ModSort,
+    watch_mods: bool,
+}
+
+impl Default for ManagerSettings {
+    fn default() -> Self {
+        ManagerSettings {
+            exit_delay_secs: 4,
+            install_strategy: InstallMethod::Copy,
+            mod_sort: ModSort::Name,
+            watch_mods: false,
+        }
+    }
+}
+
+impl ManagerSettings {
+    /// How long the goodbye message stays on screen before the program closes.
+    pub fn exit_delay(&self) -> Duration {
+        Duration::from_secs(self.exit_delay_secs)
+    }
+
+    /// How switches put the mod files in the game folders.
+    pub fn install_strategy(&self) -> InstallMethod {
+        self.install_strategy
+    }
+
+    /// The order the mod selector starts with.
+    pub fn mod_sort(&self) -> ModSort {
+        self.mod_sort
+    }
+
+    /// Whether the menu and the full-screen interface pick up mods added, removed or changed in
+    /// the mods group folder while they're open.
+    pub fn watch_mods(&self) -> bool {
+        self.watch_mods
+    }
+}
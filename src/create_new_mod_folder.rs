@@ -1,30 +1,43 @@
 use std::{fs::create_dir, io, ops::Not, path::Path};
 
-use inquire::{error::InquireResult, InquireError, Text};
+use inquire::{error::InquireResult, InquireError};
 use thiserror::Error;
 
 use crate::{
     configs::paths_config::{ModsGroupPath, ProgramPath},
+    functions::get_user_input,
     hotline_mod::{HotlineModName, MODS_FOLDER_NAME, MUSIC_FOLDER_NAME},
-    manager::AllMods,
+    mod_manager::AllMods,
+    prompter::Prompter,
 };
 
 const GET_NEW_MOD_NAME_PROMPT: &str =
     "What will be the mod's name? (Please use the snake case identifier)";
 
+pub fn prompt_new_mod_name(
+    prompter: &mut dyn Prompter,
+    all_mods: &AllMods,
+) -> Result<String, CreateNewModFolderError> {
+    match get_new_mod_name(prompter, all_mods) {
+        Ok(new_mod_name) => Ok(new_mod_name),
+        Err(InquireError::OperationCanceled) => Err(CreateNewModFolderError::UserCanceledOperation),
+        Err(InquireError::OperationInterrupted) => {
+            Err(CreateNewModFolderError::UserExitedApplication)
+        }
+        Err(err) => Err(From::from(err)),
+    }
+}
+
 pub fn create_new_mod_folder(
+    name: &str,
     all_mods: &AllMods,
     mods_group_path: &ModsGroupPath,
 ) -> Result<HotlineModName, CreateNewModFolderError> {
-    let new_mod_name = match get_new_mod_name(all_mods) {
-        Ok(new_mod) => new_mod,
-        Err(InquireError::OperationCanceled) => {
-            return Err(CreateNewModFolderError::UserCanceledOperation)
-        }
-        Err(InquireError::OperationInterrupted) => panic!("User asked to quit the program"),
-        Err(err) => return Err(From::from(err)),
-    };
+    if !is_new_mod_name_valid(name, all_mods) {
+        return Err(CreateNewModFolderError::ModAlreadyExists(name.into()));
+    }
 
+    let new_mod_name = HotlineModName::from_directory(name);
     create_all_mods_dirs(&new_mod_name, mods_group_path)?;
 
     Ok(new_mod_name)
@@ -34,20 +47,24 @@ pub fn create_new_mod_folder(
 pub enum CreateNewModFolderError {
     #[error("User pressed ESC when prompted.")]
     UserCanceledOperation,
+    #[error("User wants to exit the application.")]
+    UserExitedApplication,
+    #[error("There's already a mod called {0} in your mods directory.")]
+    ModAlreadyExists(Box<str>),
     #[error("Inquire error")]
     InquireError(#[from] InquireError),
     #[error("Error creating the folder for path {1}. Error: {0}")]
     CreatingFolderError(io::Error, Box<Path>),
 }
 
-fn get_new_mod_name(all_mods: &AllMods) -> InquireResult<HotlineModName> {
-    match Text::new(&format!("{GET_NEW_MOD_NAME_PROMPT}\n")).prompt() {
-        Ok(new_name) if is_new_mod_name_valid(&new_name, all_mods) => {
-            Ok(HotlineModName::from_directory(new_name))
-        }
+fn get_new_mod_name(prompter: &mut dyn Prompter, all_mods: &AllMods) -> InquireResult<String> {
+    match get_user_input(prompter, GET_NEW_MOD_NAME_PROMPT) {
+        Ok(new_name) if is_new_mod_name_valid(&new_name, all_mods) => Ok(new_name),
         Ok(new_mod_name) => {
-            println!("There's already a mod called {new_mod_name} in your mods directory. Please provide another one.");
-            get_new_mod_name(all_mods)
+            prompter.warn(&CreateNewModFolderError::ModAlreadyExists(
+                new_mod_name.into(),
+            ));
+            get_new_mod_name(prompter, all_mods)
         }
         Err(err) => Err(err),
     }
@@ -1,7 +1,7 @@
 use std::{thread, time::Duration};
 
-pub fn exit() -> ! {
+pub fn exit(delay: Duration) -> ! {
     println!("See you soon! 😁");
-    thread::sleep(Duration::from_secs(4));
+    thread::sleep(delay);
     std::process::exit(0)
 }
@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{configs::config_dir::ConfigDir, functions::write_atomically};
+
+/// The SHA-256 of a file, or of everything inside a folder, as lowercase hex.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct FileHash(Box<str>);
+
+impl FileHash {
+    /// Hashes a file's bytes. For a folder, hashes the relative path and hash of every file
+    /// inside it, in name order.
+    pub fn of_path(path: &Path) -> io::Result<Self> {
+        let mut hasher = Sha256::new();
+
+        if fs::metadata(path)?.is_dir() {
+            hash_dir(&mut hasher, path, Path::new(""))?;
+        } else {
+            io::copy(&mut File::open(path)?, &mut hasher)?;
+        }
+
+        Ok(Self::from_digest(&hasher.finalize()))
+    }
+
+    /// Hashes everything `reader` has left.
+    pub fn of_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut hasher = Sha256::new();
+        io::copy(&mut reader, &mut hasher)?;
+
+        Ok(Self::from_digest(&hasher.finalize()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn from_digest(digest: &[u8]) -> Self {
+        let mut hex = String::with_capacity(digest.len() * 2);
+        for byte in digest {
+            let _ = write!(hex, "{byte:02x}");
+        }
+        FileHash(hex.into())
+    }
+}
+
+impl fmt::Display for FileHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn hash_dir(hasher: &mut Sha256, dir: &Path, relative_dir: &Path) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+
+    for entry in entries {
+        let path = entry.path();
+        let relative_path = relative_dir.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            hash_dir(hasher, &path, &relative_path)?;
+        } else {
+            hasher.update(relative_path.to_string_lossy().as_bytes());
+            hasher.update(FileHash::of_path(&path)?.as_str().as_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+/// Remembers file hashes by path, size and modification time, so unchanged files aren't read
+/// again.
+#[derive(Debug, Default)]
+pub struct HashCache {
+    entries: HashMap<PathBuf, CacheEntry>,
+    changed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+    sha256: FileHash,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    #[serde(default)]
+    files: Vec<CacheEntry>,
+}
+
+impl HashCache {
+    /// Starts empty when the cache file is missing or can't be read.
+    pub fn load(config_dir: &ConfigDir) -> Self {
+        let entries = fs::read_to_string(config_dir.join(HASH_CACHE_FILE_NAME))
+            .ok()
+            .and_then(|contents| toml::from_str::<CacheFile>(&contents).ok())
+            .unwrap_or_default()
+            .files
+            .into_iter()
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+
+        HashCache {
+            entries,
+            changed: false,
+        }
+    }
+
+    /// Writes the cache if a hash was added since it was loaded. Failing to write it only makes
+    /// the next run slower, so errors are ignored.
+    pub fn save(&mut self, config_dir: &ConfigDir) {
+        if !self.changed {
+            return;
+        }
+
+        let mut files = self.entries.values().cloned().collect::<Vec<_>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if let Ok(contents) = toml::to_string_pretty(&CacheFile { files }) {
+            let written = fs::create_dir_all(config_dir.path()).and_then(|()| {
+                write_atomically(&config_dir.join(HASH_CACHE_FILE_NAME), contents.as_bytes())
+            });
+            self.changed = written.is_err();
+        }
+    }
+
+    pub fn hash(&mut self, path: &Path) -> io::Result<FileHash> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        if let Some(entry) = self.entries.get(path).filter(|entry| {
+            entry.size == metadata.len()
+                && entry.modified_secs == modified.as_secs()
+                && entry.modified_nanos == modified.subsec_nanos()
+        }) {
+            return Ok(entry.sha256.clone());
+        }
+
+        let sha256 = FileHash::of_path(path)?;
+        self.entries.insert(
+            path.to_path_buf(),
+            CacheEntry {
+                path: path.to_path_buf(),
+                size: metadata.len(),
+                modified_secs: modified.as_secs(),
+                modified_nanos: modified.subsec_nanos(),
+                sha256: sha256.clone(),
+            },
+        );
+        self.changed = true;
+
+        Ok(sha256)
+    }
+
+    /// Whether both files have the same contents, comparing the sizes before hashing.
+    ///
+    /// Folders are always hashed in full, since their modification time doesn't change when a
+    /// file deep inside them does.
+    pub fn same_contents(&mut self, a: &Path, b: &Path) -> io::Result<bool> {
+        let (a_metadata, b_metadata) = (fs::metadata(a)?, fs::metadata(b)?);
+
+        if a_metadata.is_dir() || b_metadata.is_dir() {
+            return Ok(a_metadata.is_dir() == b_metadata.is_dir()
+                && FileHash::of_path(a)? == FileHash::of_path(b)?);
+        }
+
+        if a_metadata.len() != b_metadata.len() {
+            return Ok(false);
+        }
+
+        Ok(self.hash(a)? == self.hash(b)?)
+    }
+}
+
+pub const HASH_CACHE_FILE_NAME: &str = "hash_cache.toml";
@@ -0,0 +1,163 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    configs::paths_config::{GamePath, ModsPath},
+    file_hash::HashCache,
+    hotline_mod::{HotlineMod, HotlineModName, Music},
+    replace_mod::{
+        replace_mods::{installed_patchwads, is_patchwad, ReplaceModError},
+        replace_music::game_music_path,
+    },
+};
+
+/// What the game folders actually contain, found by comparing their files with the library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstalledMod {
+    /// The music and patchwads are exactly the ones of this mod.
+    Active(HotlineModName),
+    /// Some of the files are this mod's, but others are missing, changed or extra.
+    Modified(HotlineModName),
+    /// Nothing matches any mod in the library.
+    Unknown,
+}
+
+impl fmt::Display for InstalledMod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstalledMod::Active(name) => write!(f, "{name}"),
+            InstalledMod::Modified(name) => write!(f, "{name} (modified)"),
+            InstalledMod::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// Finds which of the candidates is installed.
+///
+/// A candidate without music is expected to use the fallback music, like a switch would install.
+/// When several candidates match equally, the preferred one wins, which is usually the mod the
+/// configuration says is in use.
+pub fn detect_installed_mod<'a>(
+    game_path: &GamePath,
+    mods_path: &ModsPath,
+    candidates: impl IntoIterator<Item = &'a HotlineMod>,
+    fallback_music: Option<&Music>,
+    preferred: Option<&HotlineModName>,
+    cache: &mut HashCache,
+) -> Result<InstalledMod, FingerprintError> {
+    let installed_music = Some(game_music_path(game_path)).filter(|path| path.is_file());
+    let installed_patchwads = installed_patchwads(mods_path)?;
+
+    let mut best: Option<(&HotlineMod, Match)> = None;
+
+    for candidate in candidates {
+        let expected_music = candidate.music().or(fallback_music);
+        let music_matches = match (expected_music, &installed_music) {
+            (None, _) => true,
+            (Some(expected), Some(installed)) => cache
+                .same_contents(expected.path(), installed)
+                .map_err(FingerprintError::Hashing)?,
+            (Some(_), None) => false,
+        };
+
+        let expected_patchwads = candidate
+            .mods()
+            .mods()
+            .iter()
+            .filter(|path| is_patchwad(path))
+            .collect::<Vec<_>>();
+        let mut identical_patchwads = 0;
+        let mut score = 0;
+        for expected in &expected_patchwads {
+            match installed_twin(expected, &installed_patchwads, cache)? {
+                Twin::Identical => {
+                    identical_patchwads += 1;
+                    score += 2;
+                }
+                Twin::Changed => score += 1,
+                Twin::Missing => {}
+            }
+        }
+        // Falling back to the default music is something every mod without music shares, so it
+        // doesn't count towards a partial match.
+        if music_matches && candidate.music().is_some() {
+            score += 2;
+        }
+
+        let exact = music_matches
+            && identical_patchwads == expected_patchwads.len()
+            && identical_patchwads == installed_patchwads.len();
+        let is_preferred = Some(candidate.name()) == preferred;
+        let candidate_match = Match {
+            exact,
+            preferred_exact: exact && is_preferred,
+            preferred_partial: score > 0 && is_preferred,
+            score,
+        };
+
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_match)| candidate_match > *best_match)
+        {
+            best = Some((candidate, candidate_match));
+        }
+    }
+
+    Ok(match best {
+        Some((hm_mod, found)) if found.exact => InstalledMod::Active(hm_mod.name().clone()),
+        Some((hm_mod, found)) if found.score > 0 => InstalledMod::Modified(hm_mod.name().clone()),
+        _ => InstalledMod::Unknown,
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum FingerprintError {
+    #[error("{0}")]
+    ReadingModsDirectory(#[from] ReplaceModError),
+    #[error("Error reading the installed files to find out which mod is in use. Error: {0}")]
+    Hashing(io::Error),
+}
+
+/// How well a mod matches the installed files. The fields are compared in order, so exact
+/// matches come first and the preferred mod wins over others that match as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Match {
+    exact: bool,
+    preferred_exact: bool,
+    preferred_partial: bool,
+    score: usize,
+}
+
+enum Twin {
+    Identical,
+    Changed,
+    Missing,
+}
+
+/// Looks for an installed patchwad with the same name, and compares their contents.
+fn installed_twin(
+    expected: &Path,
+    installed_patchwads: &[PathBuf],
+    cache: &mut HashCache,
+) -> Result<Twin, FingerprintError> {
+    let Some(installed) = installed_patchwads
+        .iter()
+        .find(|installed| installed.file_name() == expected.file_name())
+    else {
+        return Ok(Twin::Missing);
+    };
+
+    let identical = cache
+        .same_contents(expected, installed)
+        .map_err(FingerprintError::Hashing)?;
+
+    Ok(if identical {
+        Twin::Identical
+    } else {
+        Twin::Changed
+    })
+}
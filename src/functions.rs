@@ -1,33 +1,44 @@
 use std::{
+    ffi::OsString,
     fmt::Display,
-    fs::{read_dir, DirEntry},
+    fs::{self, read_dir, DirEntry, File},
+    io::{self, Write},
     iter::Iterator,
-    path::Path,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
-use inquire::{error::InquireResult, InquireError, Select, Text};
-
-pub fn get_user_input(prompt: &str) -> String {
-    Text::new(&format!("{prompt}\n"))
-        .prompt()
-        .unwrap_or_else(|err| {
-            if let InquireError::OperationInterrupted | InquireError::OperationCanceled = err {
-                panic!("user exited the program.")
-            } else {
-                println!("We couldn't handle your input. Please try again.");
-                get_user_input(prompt)
-            }
-        })
+use inquire::{error::InquireResult, InquireError};
+
+use crate::prompter::Prompter;
+
+pub fn get_user_input(prompter: &mut dyn Prompter, prompt: &str) -> InquireResult<String> {
+    match prompter.text(&format!("{prompt}\n")) {
+        Err(
+            err @ (InquireError::OperationInterrupted
+            | InquireError::OperationCanceled
+            | InquireError::NotTTY),
+        ) => Err(err),
+        Err(_) => {
+            prompter.warn(&"We couldn't handle your input. Please try again.");
+            get_user_input(prompter, prompt)
+        }
+        input => input,
+    }
 }
 
 pub fn prompt_user_select<T: Display>(
+    prompter: &mut dyn Prompter,
     message: impl AsRef<str>,
     options: Vec<T>,
 ) -> InquireResult<T> {
-    Select::new(message.as_ref(), options)
-        .with_page_size(20)
-        .with_help_message("Press ESC to go back.")
-        .prompt()
+    let labels = options.iter().map(ToString::to_string).collect::<Vec<_>>();
+    let index = prompter.select(message.as_ref(), &labels, Some("Press ESC to go back."))?;
+
+    Ok(options
+        .into_iter()
+        .nth(index)
+        .expect("Prompter returns the index of one of the options"))
 }
 
 pub fn get_dirs(path: &Path) -> std::io::Result<Vec<DirEntry>> {
@@ -36,6 +47,78 @@ pub fn get_dirs(path: &Path) -> std::io::Result<Vec<DirEntry>> {
         .map(Iterator::collect)
 }
 
+/// Replaces the file at `path` with `contents` so that it is never left half-written.
+///
+/// The contents go to a temporary file next to it, which is flushed to disk and then renamed
+/// over the original.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temp_file_name = path
+        .file_name()
+        .map_or_else(OsString::new, ToOwned::to_owned);
+    temp_file_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_file_name);
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    sync_parent_dir(path);
+
+    Ok(())
+}
+
+/// Makes the rename itself durable. Directories can't be opened as files on Windows, so this is
+/// best effort.
+fn sync_parent_dir(path: &Path) {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    if let Ok(dir) = File::open(parent) {
+        _ = dir.sync_all();
+    }
+}
+
+/// `dir`, or the same name with a number after it when it's taken, like `patchwads_backup_2`.
+pub fn available_dir(dir: &Path) -> PathBuf {
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+
+    let mut candidate = dir.to_path_buf();
+    let mut number = 2;
+    while candidate.exists() {
+        candidate = dir.with_file_name(format!("{name}_{number}"));
+        number += 1;
+    }
+
+    candidate
+}
+
+/// How long ago `time` was, like `3 days ago`.
+pub fn time_ago(time: SystemTime) -> String {
+    let elapsed = time.elapsed().unwrap_or(Duration::ZERO).as_secs();
+    let (count, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3_600 => (elapsed / 60, "minute"),
+        3_600..86_400 => (elapsed / 3_600, "hour"),
+        _ => (elapsed / 86_400, "day"),
+    };
+
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
 pub fn capitalize(value: &str) -> String {
     let mut result = String::with_capacity(value.len());
     if let Some(first_char) = value.chars().next() {
@@ -46,6 +129,7 @@ pub fn capitalize(value: &str) -> String {
     result
 }
 
+
 pub fn work_in_progress() {
     println!("🏗 🏗  Work in progress 🏗 🏗");
 }
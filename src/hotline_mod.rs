@@ -1,25 +1,58 @@
-use std::{fmt, fs::ReadDir, path::Path, rc::Rc};
+use std::{
+    fmt,
+    fs::{self, ReadDir},
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use crate::functions::capitalize;
+use crate::{functions::capitalize, mod_manifest::ModManifest};
 
 pub const VALID_MUSIC_EXTENSION: &str = "wad";
 pub const MUSIC_FOLDER_NAME: &str = "music";
 pub const MODS_FOLDER_NAME: &str = "mods";
+pub const MUSIC_TRACKS_FOLDER_NAME: &str = "music_tracks";
 
 #[derive(Debug, Clone)]
 pub struct HotlineMod {
     name: HotlineModName,
     music: Option<Music>,
+    music_tracks: Option<MusicTracks>,
     mods: AssociatedMods,
+    manifest: Option<Rc<ModManifest>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Music(Rc<Path>);
 
 impl Music {
+    pub fn new(path: &Path) -> Self {
+        Music(Rc::from(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// A folder of audio files that replace single tracks of the music.
+#[derive(Debug, Clone)]
+pub struct MusicTracks(Rc<Path>);
+
+impl MusicTracks {
     pub fn path(&self) -> &Path {
         &self.0
     }
+
+    /// Every file inside the folder, named by its path relative to it with `/` between folders,
+    /// like `Music/Hotline.ogg`, in name order.
+    pub fn tracks(&self) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut tracks = Vec::new();
+        list_tracks(&self.0, "", &mut tracks)?;
+        tracks.sort();
+
+        Ok(tracks)
+    }
 }
 
 impl AsRef<Path> for Music {
@@ -44,19 +77,51 @@ impl fmt::Display for HotlineMod {
 }
 
 impl HotlineMod {
+    /// Reads the mod folder. A `mod.toml` that can't be read is left out, like a mod without
+    /// one.
     pub fn new(mod_path: &Path) -> Option<HotlineMod> {
-        let name = get_name(mod_path)?;
+        let manifest = get_manifest(mod_path);
+        let name = get_name(mod_path)?.with_manifest(manifest.as_deref());
         let music = get_music(mod_path);
+        let music_tracks = get_music_tracks(mod_path);
         let mods = get_mods(mod_path);
-        Some(HotlineMod { name, music, mods })
+        Some(HotlineMod {
+            name,
+            music,
+            music_tracks,
+            mods,
+            manifest,
+        })
     }
 
     pub fn from_name(name: HotlineModName) -> HotlineMod {
         let mod_path = name.directory_name();
+        let manifest = get_manifest(mod_path);
         HotlineMod {
             music: get_music(mod_path),
+            music_tracks: get_music_tracks(mod_path),
             mods: get_mods(mod_path),
+            name: name.with_manifest(manifest.as_deref()),
+            manifest,
+        }
+    }
+
+    /// A mod made of music alone, kept somewhere other than the mods group folder.
+    pub fn from_music(name: HotlineModName, music: &Path) -> HotlineMod {
+        HotlineMod {
             name,
+            music: Some(Music(Rc::from(music))),
+            music_tracks: None,
+            mods: AssociatedMods(Rc::new([])),
+            manifest: None,
+        }
+    }
+
+    /// The same mod with another music, like the one its music tracks were patched into.
+    pub fn with_music(&self, music: &Path) -> HotlineMod {
+        HotlineMod {
+            music: Some(Music(Rc::from(music))),
+            ..self.clone()
         }
     }
 
@@ -68,9 +133,38 @@ impl HotlineMod {
         self.music.as_ref()
     }
 
+    /// The `music_tracks` folder, when the mod has one.
+    pub fn music_tracks(&self) -> Option<&MusicTracks> {
+        self.music_tracks.as_ref()
+    }
+
     pub fn mods(&self) -> &AssociatedMods {
         &self.mods
     }
+
+    /// What the mod's `mod.toml` says about it, when it has one.
+    pub fn manifest(&self) -> Option<&ModManifest> {
+        self.manifest.as_deref()
+    }
+
+    /// The size of the mod's music, music tracks and mods files, leaving out any that can't be
+    /// read.
+    pub fn size(&self) -> u64 {
+        let tracks = self
+            .music_tracks()
+            .and_then(|music_tracks| music_tracks.tracks().ok())
+            .unwrap_or_default();
+
+        self.music()
+            .map(|music| music.path().to_path_buf())
+            .into_iter()
+            .chain(self.mods().mods().iter().map(|path| path.to_path_buf()))
+            .chain(tracks.into_iter().map(|(_, path)| path))
+            .filter_map(|path| fs::metadata(path).ok())
+            .filter(fs::Metadata::is_file)
+            .map(|metadata| metadata.len())
+            .sum()
+    }
 }
 
 fn get_name(mod_path: &Path) -> Option<HotlineModName> {
@@ -78,7 +172,9 @@ fn get_name(mod_path: &Path) -> Option<HotlineModName> {
 
     Some(HotlineModName::from_directory(directory_name))
 }
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The name of a mod, which is its folder name. Two names are the same when their folders are,
+/// whatever name is shown for them.
+#[derive(Debug, Clone)]
 pub struct HotlineModName {
     dir_name: Rc<Path>,
     formatted_name: Rc<str>,
@@ -101,6 +197,17 @@ impl HotlineModName {
         }
     }
 
+    /// Shows the name from the mod's `mod.toml` instead of the one made from the folder name.
+    fn with_manifest(self, manifest: Option<&ModManifest>) -> Self {
+        match manifest.and_then(ModManifest::name) {
+            Some(name) => HotlineModName {
+                formatted_name: name.trim().into(),
+                ..self
+            },
+            None => self,
+        }
+    }
+
     pub fn directory_name(&self) -> &Path {
         &self.dir_name
     }
@@ -110,6 +217,14 @@ impl HotlineModName {
     }
 }
 
+impl PartialEq for HotlineModName {
+    fn eq(&self, other: &Self) -> bool {
+        self.dir_name == other.dir_name
+    }
+}
+
+impl Eq for HotlineModName {}
+
 impl fmt::Display for HotlineModName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.formatted_name)
@@ -127,6 +242,31 @@ fn get_music(mod_path: &Path) -> Option<Music> {
         .map(Music)
 }
 
+fn get_manifest(mod_path: &Path) -> Option<Rc<ModManifest>> {
+    ModManifest::read(mod_path).ok().flatten().map(Rc::new)
+}
+
+fn get_music_tracks(mod_path: &Path) -> Option<MusicTracks> {
+    let path = mod_path.join(MUSIC_TRACKS_FOLDER_NAME);
+
+    path.is_dir().then(|| MusicTracks(Rc::from(path)))
+}
+
+fn list_tracks(dir: &Path, prefix: &str, tracks: &mut Vec<(String, PathBuf)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+
+        if entry.file_type()?.is_dir() {
+            list_tracks(&entry.path(), &format!("{name}/"), tracks)?;
+        } else {
+            tracks.push((name, entry.path()));
+        }
+    }
+
+    Ok(())
+}
+
 fn get_mods(mod_path: &Path) -> AssociatedMods {
     let associated_mods = mod_path
         .join(MODS_FOLDER_NAME)
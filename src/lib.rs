@@ -0,0 +1,32 @@
+#![warn(clippy::pedantic)]
+#![warn(clippy::perf)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::return_self_not_must_use)]
+#![allow(clippy::missing_panics_doc)]
+
+pub mod actions;
+pub mod change_configuration_path;
+pub mod cli;
+pub mod compose_music;
+pub mod configs;
+pub mod create_new_mod_folder;
+pub mod exit;
+pub mod file_hash;
+pub mod fingerprint;
+pub mod functions;
+pub mod hotline_mod;
+pub mod manager;
+pub mod merge_patchwads;
+pub mod mod_manager;
+pub mod mod_manifest;
+pub mod mods_watcher;
+pub mod music_tracks;
+pub mod picker;
+pub mod prompter;
+pub mod replace_mod;
+pub mod run_game;
+pub mod select_mod;
+pub mod tui;
+pub mod vanilla;
+pub mod wad;
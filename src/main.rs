@@ -5,28 +5,38 @@
 #![allow(clippy::return_self_not_must_use)]
 #![allow(clippy::missing_panics_doc)]
 
-use manager::HotlineModManager;
-use run_game::run_hotline_miami_2;
+use std::process::ExitCode;
 
-pub mod actions;
-pub mod change_configuration_path;
-pub mod configs;
-pub mod create_new_mod_folder;
-pub mod exit;
-pub mod functions;
-pub mod hotline_mod;
-pub mod manager;
-pub mod replace_mod;
-pub mod run_game;
-pub mod select_mod;
+use clap::Parser;
+use hotline_miami_mod_manager::{cli::Cli, exit::exit, manager::HotlineModManager};
 
-fn main() {
-    let manager = HotlineModManager::build();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let config_dir = match cli.config_dir() {
+        Ok(config_dir) => config_dir,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(command) = cli.command() {
+        return match command.execute(config_dir) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{err}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let manager = HotlineModManager::build(config_dir);
 
     match manager {
         Ok(mut manager) => {
             manager.run();
-            std::process::exit(0);
+            exit(manager.mod_manager().configs().settings().exit_delay());
         }
         Err(err) => {
             println!("{err}");
@@ -1,257 +1,301 @@
 use crate::{
     change_configuration_path::get_desired_path_to_change,
-    configs::paths_config::ConfigurationPath, exit::exit,
+    configs::config_dir::ConfigDir,
+    fingerprint::InstalledMod,
+    hotline_mod::HotlineModName,
+    mod_manager::{ModManager, SwitchReport, ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING},
+    prompter::{InquirePrompter, Prompter},
 };
-use std::{fs, ops::Deref};
 
 use anyhow::{anyhow, bail};
-use inquire::{InquireError, Select};
+use inquire::InquireError;
+use thiserror::Error;
 
 use crate::{
     actions::Action,
-    configs::{
-        paths_config::{ModsGroupPath, ProgramPath},
-        Configs,
-    },
-    create_new_mod_folder::create_new_mod_folder,
-    hotline_mod::{HotlineMod, Music},
-    replace_mod::{replace_mods::replace_mods, replace_music::replace_music},
+    create_new_mod_folder::{prompt_new_mod_name, CreateNewModFolderError},
     select_mod::{select_mod, ChangeCurrentModError},
 };
 
 pub struct HotlineModManager {
-    default_game: Option<DefaultHotlineMod>,
-    all_mods: AllMods,
-    configs: Configs,
+    mod_manager: ModManager,
+    prompter: Box<dyn Prompter>,
 }
 
 impl HotlineModManager {
-    pub fn build() -> anyhow::Result<Self> {
-        let configs = Configs::build()?;
-        let mut all_mods = list_mods(configs.paths_config().mods_group_path())?;
-        let default_game_index = all_mods.0.iter().position(|hm_mod| {
-            hm_mod.name().directory_name().to_string_lossy() == "hotline_miami_2"
-        });
-
-        let default_game = default_game_index
-            .map(|index| all_mods.0.remove(index))
-            .map(DefaultHotlineMod);
-
-        if default_game.is_none() {
+    pub fn build(config_dir: ConfigDir) -> anyhow::Result<Self> {
+        Self::with_prompter(config_dir, Box::new(InquirePrompter))
+    }
+
+    pub fn with_prompter(
+        config_dir: ConfigDir,
+        mut prompter: Box<dyn Prompter>,
+    ) -> anyhow::Result<Self> {
+        let mut mod_manager = ModManager::build(config_dir, prompter.as_mut())?;
+        print_config_warnings(&mod_manager);
+
+        // Keeps the original music before the first switch can replace it.
+        if let Err(err) = mod_manager.vault_original_music() {
+            println!("{err}");
+        }
+        if mod_manager.default_game().is_none() {
             println!("{ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING}");
         }
+        watch_mods(&mut mod_manager);
 
         Ok(HotlineModManager {
-            default_game,
-            all_mods,
-            configs,
+            mod_manager,
+            prompter,
         })
     }
 
+    pub fn mod_manager(&self) -> &ModManager {
+        &self.mod_manager
+    }
+
+    /// Runs the menu until the user asks to leave.
     pub fn run(&mut self) {
         loop {
+            self.refresh_mods();
             let action = self.get_action();
 
-            if let Err(error) = match action {
+            let result = match action {
                 Action::ChangeMod => self.change_mod(),
                 Action::RunGame => Self::run_hotline_miami_2(),
                 Action::UseDefaultSettings => self.use_default_settings(),
+                Action::VerifyVanilla => self.verify_vanilla(),
                 Action::CreateNewModFolder => self.create_new_mod_folder(),
+                Action::RescanLibrary => self.rescan_library(),
                 Action::ChangeConfigurationPath => self.change_configuration_path(),
                 Action::ClearConfiguration => self.clear_configuration(),
-                Action::Exit => exit(),
-            } {
-                println!("{error}");
+                Action::Exit => return,
+            };
+
+            match result {
+                Ok(()) => {}
+                Err(error) if error.is::<UserExitedApplication>() => return,
+                Err(error) => println!("{error}"),
             }
         }
     }
 
-    fn get_action(&self) -> Action {
-        self.print_mod_name();
-        let prompt =
-            Select::new("What do you want to do?", Action::VARIANTS.iter().collect()).prompt();
+    fn get_action(&mut self) -> Action {
+        print_mod_name(&mut self.mod_manager);
+        let labels = Action::VARIANTS
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let prompt = self
+            .prompter
+            .select("What do you want to do?", &labels, None);
 
         match prompt {
-            Ok(action) => *action,
+            Ok(index) => Action::VARIANTS[index],
             Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
-                panic!("User asked to quit the program")
+                Action::Exit
+            }
+            Err(error) => {
+                println!("{error}");
+                Action::Exit
             }
-            Err(error) => panic!("{}", error),
         }
     }
 
     fn change_mod(&mut self) -> anyhow::Result<()> {
-        let desired_mod = match select_mod(&self.all_mods.0) {
+        let desired_mod = match select_mod(
+            self.prompter.as_mut(),
+            self.mod_manager.all_mods().mods(),
+            self.mod_manager.configs(),
+        ) {
             Ok(desired_mod) => desired_mod,
             Err(ChangeCurrentModError::EmptyMods) => {
                 println!("You have no mods in your folder right now. Try downloading new mods or bringing your existing mods to this folder.");
                 return Ok(());
             }
             Err(ChangeCurrentModError::UserCanceledOperation) => return Ok(()),
-            Err(ChangeCurrentModError::UserExitedApplication) => {
-                panic!("User requested to leave the application.")
-            }
+            Err(ChangeCurrentModError::UserExitedApplication) => bail!(UserExitedApplication),
             Err(ChangeCurrentModError::InquireError(err)) => bail!(err),
         };
 
-        let default_game_music = self.default_game_music();
-
-        let music = desired_mod.music().or(default_game_music);
-
-        if let Some(music) = music {
-            replace_music(
-                self.configs.paths_config().game_path(),
-                music,
-                desired_mod.name(),
-            )?;
-        } else {
-            println!("{ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING}");
+        let plan = self.mod_manager.plan_switch(&desired_mod)?;
+        print!("{plan}");
+        if plan.blocked_by().is_some() {
+            println!("Move the file out of your mods folder and try again.");
+            return Ok(());
+        }
+        if !plan.corrupt().is_empty() {
+            println!("Download the mod again and try again.");
+            return Ok(());
         }
 
-        replace_mods(
-            self.configs.paths_config().mods_path(),
-            desired_mod.mods(),
-            desired_mod.name(),
-        )?;
+        match self.prompter.confirm("Apply this switch?", true) {
+            Ok(true) => {}
+            Ok(false) | Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(InquireError::OperationInterrupted) => bail!(UserExitedApplication),
+            Err(err) => bail!(err),
+        }
 
-        self.configs.set_current_mod(desired_mod.name().clone())?;
+        let report = self.mod_manager.switch_mod(&desired_mod)?;
+        print_warnings(&report);
 
         Ok(())
     }
 
-    fn default_game_music(&self) -> Option<&Music> {
-        self.default_game
-            .as_ref()
-            .and_then(|default_mod| default_mod.music())
-    }
-
     fn run_hotline_miami_2() -> anyhow::Result<()> {
-        if let Err(err) = crate::run_hotline_miami_2() {
+        if let Err(err) = crate::run_game::run_hotline_miami_2() {
             Err(anyhow!(err))
         } else {
-            std::process::exit(0)
+            bail!(UserExitedApplication)
         }
     }
 
     fn use_default_settings(&mut self) -> anyhow::Result<()> {
-        match &self.default_game {
-            None => {
-                println!("{ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING}");
-                Ok(())
-            }
-            Some(DefaultHotlineMod(hm_mod)) if hm_mod.music().is_none() => {
-                println!("{ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING}");
-                Ok(())
-            }
-            Some(DefaultHotlineMod(hm_mod)) => {
-                replace_music(
-                    self.configs.paths_config().game_path(),
-                    hm_mod.music().expect("Music validation in above branch"),
-                    hm_mod.name(),
-                )?;
-                replace_mods(
-                    self.configs.paths_config().mods_path(),
-                    hm_mod.mods(),
-                    hm_mod.name(),
-                )?;
-
-                self.configs.set_current_mod(hm_mod.name().clone())?;
-
-                Ok(())
-            }
+        let report = self.mod_manager.use_default_settings()?;
+        print_warnings(&report);
+
+        Ok(())
+    }
+
+    fn verify_vanilla(&mut self) -> anyhow::Result<()> {
+        let report = self.mod_manager.verify_vanilla()?;
+        print!("{report}");
+        if report.is_vanilla() {
+            return Ok(());
+        }
+
+        match self.prompter.confirm(
+            "Restore the vanilla game? Your patchwads are moved aside, not deleted.",
+            false,
+        ) {
+            Ok(true) => {}
+            Ok(false) | Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(InquireError::OperationInterrupted) => bail!(UserExitedApplication),
+            Err(err) => bail!(err),
         }
+
+        for path in self.mod_manager.restore_vanilla()? {
+            println!("Moved a patchwad aside to {}", path.display());
+        }
+        print!("{}", self.mod_manager.verify_vanilla()?);
+
+        Ok(())
     }
 
     fn clear_configuration(&mut self) -> anyhow::Result<()> {
-        self.configs.clear()?;
+        self.mod_manager.clear_configs()?;
 
-        self.configs = Configs::build()?;
+        let config_dir = self.mod_manager.configs().config_dir().clone();
+        self.mod_manager = ModManager::build(config_dir, self.prompter.as_mut())?;
+        print_config_warnings(&self.mod_manager);
+        watch_mods(&mut self.mod_manager);
 
         Ok(())
     }
 
     fn create_new_mod_folder(&mut self) -> anyhow::Result<()> {
-        let new_mod = create_new_mod_folder(
-            &self.all_mods,
-            self.configs.paths_config().mods_group_path(),
-        )?;
-        self.all_mods.0.push(HotlineMod::from_name(new_mod));
+        let new_mod_name =
+            match prompt_new_mod_name(self.prompter.as_mut(), self.mod_manager.all_mods()) {
+                Ok(new_mod_name) => new_mod_name,
+                Err(CreateNewModFolderError::UserExitedApplication) => {
+                    bail!(UserExitedApplication)
+                }
+                Err(err) => bail!(err),
+            };
+        self.mod_manager.create_mod_folder(&new_mod_name)?;
 
         Ok(())
     }
 
-    fn change_configuration_path(&mut self) -> anyhow::Result<()> {
-        match get_desired_path_to_change() {
-            Ok(ConfigurationPath::Game(path)) => {
-                self.configs.mut_paths_config().set_game_path(path.into());
-            }
-            Ok(ConfigurationPath::Mods(path)) => {
-                self.configs.mut_paths_config().set_mods_path(path.into());
-            }
-            Ok(ConfigurationPath::Group(path)) => {
-                self.configs
-                    .mut_paths_config()
-                    .set_mods_group_path(path.into());
-            }
-            Err(InquireError::OperationCanceled) => return Ok(()),
-            Err(InquireError::OperationInterrupted) => panic!("User requested to quit application"),
-            Err(err) => bail!(err),
-        }
+    fn rescan_library(&mut self) -> anyhow::Result<()> {
+        let all_mods = self.mod_manager.rescan_mods()?;
+        println!("Found {} mods.", all_mods.mods().len());
 
         Ok(())
     }
 
-    fn print_mod_name(&self) {
-        let mod_name = self
-            .configs
-            .current_mod()
-            .map(|current_mod| current_mod.name().formatted_name())
-            .map_or("Uncertain...", AsRef::as_ref);
-
-        println!("You are currently using: {mod_name}");
+    /// Picks up the changes the watcher saw in the mods group folder during the last action.
+    fn refresh_mods(&mut self) {
+        match self.mod_manager.refresh_mods() {
+            Ok(true) => println!(
+                "Your mods folder changed, found {} mods.",
+                self.mod_manager.all_mods().mods().len()
+            ),
+            Ok(false) => {}
+            Err(err) => println!("{err}"),
+        }
     }
-}
 
-pub struct DefaultHotlineMod(HotlineMod);
+    fn change_configuration_path(&mut self) -> anyhow::Result<()> {
+        match get_desired_path_to_change(self.prompter.as_mut()) {
+            Ok(configuration_path) => self
+                .mod_manager
+                .set_configuration_path(configuration_path)?,
+            Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(InquireError::OperationInterrupted) => bail!(UserExitedApplication),
+            Err(err) => bail!(err),
+        }
 
-impl DefaultHotlineMod {
-    pub fn hm_mod(&self) -> &HotlineMod {
-        self
+        Ok(())
     }
 }
 
-impl Deref for DefaultHotlineMod {
-    type Target = HotlineMod;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// Starts the watcher when the settings ask for it. The menu works without it.
+fn watch_mods(mod_manager: &mut ModManager) {
+    if mod_manager.configs().settings().watch_mods()
+        && let Err(err) = mod_manager.watch_mods()
+    {
+        println!("{err}");
     }
 }
 
-fn list_mods(mods_path: &ModsGroupPath) -> anyhow::Result<AllMods> {
-    let vec = fs::read_dir(mods_path.path())?
-        .filter_map(Result::ok)
-        .map(|entry| entry.path())
-        .filter_map(|path| HotlineMod::new(&path))
-        .collect();
-
-    Ok(AllMods::new(vec))
+fn print_config_warnings(mod_manager: &ModManager) {
+    for warning in mod_manager.configs().warnings() {
+        println!("{warning}");
+    }
 }
 
-pub struct AllMods(Vec<HotlineMod>);
-
-impl AllMods {
-    fn new(mods: Vec<HotlineMod>) -> Self {
-        AllMods(mods)
+/// Prints which mod the game folders hold, with its manifest. Shared by the menu and the CLI.
+pub fn print_mod_name(manager: &mut ModManager) {
+    match manager.detect_installed_mod() {
+        Ok(InstalledMod::Active(name)) => {
+            println!("You are currently using: {name}");
+            print_manifest(manager, &name);
+        }
+        Ok(InstalledMod::Modified(name)) => {
+            println!("You are currently using a modified version of: {name}");
+            print_manifest(manager, &name);
+        }
+        Ok(InstalledMod::Unknown) => {
+            println!("You are currently using: Unknown, the game files don't match any of your mods");
+        }
+        Err(err) => {
+            let mod_name = manager
+                .current_mod()
+                .map(|current_mod| current_mod.name().formatted_name())
+                .map_or("Uncertain...", AsRef::as_ref);
+
+            println!("You are currently using: {mod_name}");
+            eprintln!("{err}");
+        }
     }
+}
 
-    pub fn mods(&self) -> &[HotlineMod] {
-        &self.0
+/// Prints the metadata of the mod's `mod.toml`, if it has one.
+fn print_manifest(manager: &ModManager, name: &HotlineModName) {
+    match manager.read_manifest(name) {
+        Ok(Some(manifest)) => print!("{manifest}"),
+        Ok(None) => {}
+        Err(err) => eprintln!("{err}"),
     }
+}
 
-    pub fn mods_mut(&mut self) -> &mut Vec<HotlineMod> {
-        &mut self.0
+fn print_warnings(report: &SwitchReport) {
+    for warning in report.warnings() {
+        println!("{warning}");
     }
 }
 
-const ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING: &str = "Attention: You don't have a folder for the original Hotline Miami 2 with the correct configuration. It should contain the original game's music and be named 'hotline_miami_2'. Without it, the program may not behave as expected. For more information, visit the project's GitHub (https://github.com/cardosoOReis/hotline_miami_mod_manager.git).";
+/// Returned by a menu action when the user asked to leave in the middle of it.
+#[derive(Error, Debug)]
+#[error("User wants to exit the application.")]
+struct UserExitedApplication;
@@ -0,0 +1,208 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    functions::available_dir,
+    hotline_mod::{HotlineMod, MODS_FOLDER_NAME},
+    replace_mod::replace_mods::is_patchwad,
+    wad::{
+        conflicts::{sort_by_load_order, ConflictReport},
+        merge::merge_wads,
+        WadError,
+    },
+};
+
+/// What merging a mod's patchwads did.
+#[derive(Debug, Clone)]
+pub struct MergeReport {
+    merged: PathBuf,
+    sources: Vec<PathBuf>,
+    backup_dir: PathBuf,
+    conflicts: ConflictReport,
+}
+
+impl MergeReport {
+    /// The patchwad holding everything now.
+    pub fn merged(&self) -> &Path {
+        &self.merged
+    }
+
+    /// The patchwads that were merged, in the order they were loaded.
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// Where the original patchwads were moved.
+    pub fn backup_dir(&self) -> &Path {
+        &self.backup_dir
+    }
+
+    pub fn conflicts(&self) -> &ConflictReport {
+        &self.conflicts
+    }
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Merged {} patchwads into {}:",
+            self.sources.len(),
+            self.merged.display()
+        )?;
+        for (position, source) in self.sources.iter().enumerate() {
+            writeln!(f, "  {}. {}", position + 1, file_name(source))?;
+        }
+        write!(f, "{}", self.conflicts)?;
+        writeln!(f, "The originals are in {}", self.backup_dir.display())
+    }
+}
+
+/// Merges every patchwad in the mod's `mods` folder into one named after the mod, and moves the
+/// originals into a backup folder next to it.
+///
+/// `order` lists the patchwads by file name in the order they're loaded, so a later one wins
+/// when several provide the same asset. When it's empty, they're loaded in name order like the
+/// game does.
+pub fn merge_mod_patchwads(
+    hm_mod: &HotlineMod,
+    mod_dir: &Path,
+    order: &[String],
+) -> Result<MergeReport, MergePatchwadsError> {
+    let patchwads = hm_mod
+        .mods()
+        .mods()
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|path| is_patchwad(path))
+        .collect::<Vec<&Path>>();
+    if patchwads.len() < 2 {
+        return Err(MergePatchwadsError::NothingToMerge(
+            hm_mod.name().to_string().into(),
+        ));
+    }
+    let sources = ordered(patchwads, order)?;
+
+    let merged_name = format!("{}.patchwad", hm_mod.name().directory_name().display());
+    let merged = mod_dir.join(MODS_FOLDER_NAME).join(&merged_name);
+    let merging = mod_dir.join(format!(".{merged_name}.merging"));
+    let backup_dir = available_dir(&mod_dir.join(BACKUP_DIR_NAME));
+
+    let conflicts = ConflictReport::in_order(sources.iter().copied());
+    merge_wads(&sources, &merging)?;
+
+    if let Err(err) = move_into_backup(&sources, &backup_dir) {
+        let _ = fs::remove_file(&merging);
+        return Err(err);
+    }
+    if let Err(err) = fs::rename(&merging, &merged) {
+        restore_from_backup(&sources, &backup_dir);
+        let _ = fs::remove_file(&merging);
+        return Err(MergePatchwadsError::Installing(err));
+    }
+
+    Ok(MergeReport {
+        merged,
+        sources: sources.iter().map(|path| path.to_path_buf()).collect(),
+        backup_dir,
+        conflicts,
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum MergePatchwadsError {
+    #[error("{0} has less than two patchwads, so there's nothing to merge.")]
+    NothingToMerge(Box<str>),
+    #[error("The order names {0}, which isn't one of the mod's patchwads.")]
+    UnknownPatchwad(Box<str>),
+    #[error("The order names {0} more than once.")]
+    RepeatedPatchwad(Box<str>),
+    #[error(
+        "The order doesn't name {0}. List every patchwad of the mod, or none to use name order."
+    )]
+    MissingFromOrder(Box<str>),
+    #[error("{0}")]
+    Wad(#[from] WadError),
+    #[error("Error moving the original patchwads into {1}, they were put back. Error: {0}")]
+    BackingUp(io::Error, Box<Path>),
+    #[error("Error moving the merged patchwad into the mods folder, the originals were put back. Error: {0}")]
+    Installing(io::Error),
+}
+
+/// Puts the patchwads in the explicit order, or in the game's load order when there's none.
+fn ordered<'a>(
+    mut patchwads: Vec<&'a Path>,
+    order: &[String],
+) -> Result<Vec<&'a Path>, MergePatchwadsError> {
+    if order.is_empty() {
+        sort_by_load_order(&mut patchwads);
+        return Ok(patchwads);
+    }
+
+    let mut ordered = Vec::with_capacity(patchwads.len());
+    for name in order {
+        let is_named = |path: &&Path| {
+            path.file_name()
+                .is_some_and(|file_name| file_name == name.as_str())
+        };
+        if ordered.iter().any(is_named) {
+            return Err(MergePatchwadsError::RepeatedPatchwad(name.as_str().into()));
+        }
+        let position = patchwads
+            .iter()
+            .position(is_named)
+            .ok_or_else(|| MergePatchwadsError::UnknownPatchwad(name.as_str().into()))?;
+        ordered.push(patchwads.remove(position));
+    }
+
+    if let Some(missing) = patchwads.first() {
+        return Err(MergePatchwadsError::MissingFromOrder(
+            file_name(missing).into(),
+        ));
+    }
+
+    Ok(ordered)
+}
+
+/// Moves every patchwad into the backup folder, moving them back if one of them fails.
+fn move_into_backup(patchwads: &[&Path], backup_dir: &Path) -> Result<(), MergePatchwadsError> {
+    let mut moved = 0;
+
+    let result = fs::create_dir_all(backup_dir).and_then(|()| {
+        for patchwad in patchwads {
+            fs::rename(patchwad, backup_path(patchwad, backup_dir))?;
+            moved += 1;
+        }
+        Ok(())
+    });
+
+    result.map_err(|err| {
+        restore_from_backup(&patchwads[..moved], backup_dir);
+        MergePatchwadsError::BackingUp(err, backup_dir.into())
+    })
+}
+
+/// Moves the patchwads back where they were, and removes the backup folder if that leaves it
+/// empty.
+fn restore_from_backup(patchwads: &[&Path], backup_dir: &Path) {
+    for patchwad in patchwads {
+        let _ = fs::rename(backup_path(patchwad, backup_dir), patchwad);
+    }
+    let _ = fs::remove_dir(backup_dir);
+}
+
+fn backup_path(patchwad: &Path, backup_dir: &Path) -> PathBuf {
+    backup_dir.join(patchwad.file_name().unwrap_or_default())
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+}
+
+const BACKUP_DIR_NAME: &str = "patchwads_backup";
@@ -0,0 +1,885 @@
+use std::{
+    fs, io,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    compose_music::{
+        compose_mod_music, composed_music_path, ComposeMusicError, ComposeReport, TrackMusic,
+    },
+    configs::{
+        config_dir::ConfigDir,
+        current_mod_config::CurrentMod,
+        install_receipt::{InstallReceipt, InstallReceiptError},
+        music_vault::{MusicVault, MusicVaultError},
+        paths_config::{ConfigurationPath, ModsGroupPath, ProgramPath, WithPath},
+        Configs, ConfigsError,
+    },
+    create_new_mod_folder::{create_new_mod_folder, CreateNewModFolderError},
+    file_hash::HashCache,
+    fingerprint::{detect_installed_mod, FingerprintError, InstalledMod},
+    hotline_mod::{HotlineMod, HotlineModName, Music, MODS_FOLDER_NAME, MUSIC_FOLDER_NAME},
+    merge_patchwads::{merge_mod_patchwads, MergePatchwadsError, MergeReport},
+    mod_manifest::{ModManifest, ModManifestError},
+    mods_watcher::{ModsWatcher, ModsWatcherError},
+    music_tracks::{export_track, MusicTracksError, TrackDiff, TrackList},
+    prompter::Prompter,
+    replace_mod::{
+        plan::{PlannedMusic, SwitchPlan},
+        replace_mods::{installed_patchwads, ReplaceModError},
+        replace_music::game_music_path,
+        transaction::{SwitchError, SwitchTransaction},
+    },
+    vanilla::{set_aside_patchwads, VanillaError, VanillaReport},
+    wad::{
+        compose::{TrackOverride, TrackSource},
+        conflicts::ConflictReport,
+    },
+};
+
+/// The mod switching operations, free of any prompts or printing.
+pub struct ModManager {
+    default_game: Option<DefaultHotlineMod>,
+    music_vault: Option<MusicVault>,
+    all_mods: AllMods,
+    configs: Configs,
+    hash_cache: HashCache,
+    mods_watcher: Option<ModsWatcher>,
+}
+
+impl ModManager {
+    /// Loads the configuration, asking the user for the paths if none exist yet.
+    pub fn build(
+        config_dir: ConfigDir,
+        prompter: &mut dyn Prompter,
+    ) -> Result<Self, ModManagerError> {
+        Self::from_configs(Configs::build(config_dir, prompter)?)
+    }
+
+    /// Loads an existing configuration, failing instead of asking for the paths.
+    pub fn load(config_dir: ConfigDir) -> Result<Self, ModManagerError> {
+        Self::from_configs(Configs::load(config_dir)?)
+    }
+
+    pub fn from_configs(configs: Configs) -> Result<Self, ModManagerError> {
+        let (all_mods, default_game_folder) =
+            list_library(configs.paths_config().mods_group_path())?;
+
+        let mut hash_cache = HashCache::load(configs.config_dir());
+        let music_vault = MusicVault::open(configs.config_dir(), &mut hash_cache)
+            .ok()
+            .flatten();
+
+        Ok(ModManager {
+            default_game: default_game(default_game_folder, music_vault.as_ref()),
+            music_vault,
+            all_mods,
+            configs,
+            hash_cache,
+            mods_watcher: None,
+        })
+    }
+
+    pub fn configs(&self) -> &Configs {
+        &self.configs
+    }
+
+    pub fn default_game(&self) -> Option<&DefaultHotlineMod> {
+        self.default_game.as_ref()
+    }
+
+    /// The copy of the original game music, once it was vaulted.
+    pub fn music_vault(&self) -> Option<&MusicVault> {
+        self.music_vault.as_ref()
+    }
+
+    pub fn all_mods(&self) -> &AllMods {
+        &self.all_mods
+    }
+
+    pub fn current_mod(&self) -> Option<&CurrentMod> {
+        self.configs.current_mod()
+    }
+
+    /// Lists the mods group folder again, for mods added, removed or renamed since it was read.
+    pub fn rescan_mods(&mut self) -> Result<&AllMods, ModManagerError> {
+        let (all_mods, default_game_folder) =
+            list_library(self.configs.paths_config().mods_group_path())?;
+
+        self.default_game = default_game(default_game_folder, self.music_vault.as_ref());
+        self.all_mods = all_mods;
+
+        Ok(&self.all_mods)
+    }
+
+    /// Starts watching the mods group folder, so [`ModManager::refresh_mods`] rescans it once it
+    /// changed.
+    pub fn watch_mods(&mut self) -> Result<(), ModManagerError> {
+        self.mods_watcher = Some(ModsWatcher::new(
+            self.configs.paths_config().mods_group_path().path(),
+        )?);
+
+        Ok(())
+    }
+
+    /// Rescans the mods when the mods group folder changed since the last call, returning whether
+    /// it did. Does nothing unless [`ModManager::watch_mods`] was called.
+    pub fn refresh_mods(&mut self) -> Result<bool, ModManagerError> {
+        if !self
+            .mods_watcher
+            .as_ref()
+            .is_some_and(ModsWatcher::changed)
+        {
+            return Ok(false);
+        }
+
+        self.rescan_mods()?;
+        Ok(true)
+    }
+
+    /// Finds the mod the game files belong to by comparing their hashes with the library, instead
+    /// of trusting the configuration.
+    pub fn detect_installed_mod(&mut self) -> Result<InstalledMod, ModManagerError> {
+        // A mod with music tracks has the music they were patched into.
+        let candidates = self
+            .all_mods
+            .mods()
+            .iter()
+            .map(|hm_mod| {
+                let composed = composed_music_path(self.configs.config_dir(), hm_mod);
+                match hm_mod.music_tracks() {
+                    Some(_) if composed.is_file() => hm_mod.with_music(&composed),
+                    _ => hm_mod.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let paths_config = self.configs.paths_config();
+        let installed_mod = detect_installed_mod(
+            paths_config.game_path(),
+            paths_config.mods_path(),
+            candidates.iter().chain(self.default_game.as_deref()),
+            self.default_game
+                .as_ref()
+                .and_then(|default_game| default_game.music()),
+            self.configs.current_mod().map(CurrentMod::name),
+            &mut self.hash_cache,
+        )?;
+        self.hash_cache.save(self.configs.config_dir());
+
+        Ok(installed_mod)
+    }
+
+    pub fn find_mod(&self, name: &str) -> Result<&HotlineMod, ModManagerError> {
+        self.all_mods
+            .find(name)
+            .ok_or_else(|| ModManagerError::ModNotFound(name.into()))
+    }
+
+    /// Reads the mod's `mod.toml` again, failing when it can't be read instead of leaving it out
+    /// like the mod list does.
+    pub fn read_manifest(
+        &self,
+        name: &HotlineModName,
+    ) -> Result<Option<ModManifest>, ModManagerError> {
+        let mod_dir = self
+            .configs
+            .paths_config()
+            .mods_group_path()
+            .path()
+            .join(name.directory_name());
+
+        Ok(ModManifest::read(&mod_dir)?)
+    }
+
+    /// The folder of the mod inside the mods group folder.
+    pub fn mod_dir(&self, hm_mod: &HotlineMod) -> PathBuf {
+        self.configs
+            .paths_config()
+            .mods_group_path()
+            .path()
+            .join(hm_mod.name().directory_name())
+    }
+
+    pub fn switch_mod_by_name(&mut self, name: &str) -> Result<SwitchReport, ModManagerError> {
+        let desired_mod = self.find_mod(name)?.clone();
+
+        self.switch_mod(&desired_mod)
+    }
+
+    /// Installs the mod's music and patchwads, falling back to the default game's music when the
+    /// mod has none.
+    pub fn switch_mod(
+        &mut self,
+        desired_mod: &HotlineMod,
+    ) -> Result<SwitchReport, ModManagerError> {
+        self.vault_original_music()?;
+        let music = self.switch_music(desired_mod)?;
+
+        self.install(desired_mod, music.as_ref())
+    }
+
+    pub fn use_default_settings(&mut self) -> Result<SwitchReport, ModManagerError> {
+        self.vault_original_music()?;
+        let hm_mod = self.default_settings_mod()?;
+
+        self.install(&hm_mod, hm_mod.music())
+    }
+
+    /// Checks the game files against the original game: no patchwads, and the original music.
+    pub fn verify_vanilla(&mut self) -> Result<VanillaReport, ModManagerError> {
+        let original = match &self.music_vault {
+            Some(music_vault) => Some(music_vault.sha256().clone()),
+            None => self
+                .default_game
+                .as_ref()
+                .and_then(|default_game| default_game.music())
+                .map(|music| self.hash_cache.hash(music.path()))
+                .transpose()
+                .map_err(VanillaError::Hashing)?,
+        };
+
+        let paths_config = self.configs.paths_config();
+        let report = VanillaReport::check(
+            paths_config.game_path(),
+            paths_config.mods_path(),
+            original.as_ref(),
+            &mut self.hash_cache,
+        )?;
+        self.hash_cache.save(self.configs.config_dir());
+
+        Ok(report)
+    }
+
+    /// Puts the original music back and moves every patchwad left in the mods folder aside,
+    /// returning where they went.
+    pub fn restore_vanilla(&mut self) -> Result<Vec<PathBuf>, ModManagerError> {
+        self.use_default_settings()?;
+
+        Ok(set_aside_patchwads(self.configs.paths_config().mods_path())?)
+    }
+
+    /// Works out what switching to the mod does, without writing anything. Music composed from
+    /// the mod's single tracks is only planned, it's written by the switch itself.
+    pub fn plan_switch(&mut self, desired_mod: &HotlineMod) -> Result<SwitchPlan, ModManagerError> {
+        let receipt = self.install_receipt()?;
+
+        if let Some(track_music) = self.track_music(desired_mod)? {
+            return self.plan(
+                desired_mod,
+                Some(PlannedMusic::Composed(&track_music)),
+                &receipt,
+            );
+        }
+
+        let music = self.base_music(desired_mod)?.map(|music| Music::new(&music));
+        self.plan(desired_mod, music.as_ref().map(PlannedMusic::Wad), &receipt)
+    }
+
+    pub fn plan_switch_by_name(&mut self, name: &str) -> Result<SwitchPlan, ModManagerError> {
+        let desired_mod = self.find_mod(name)?.clone();
+
+        self.plan_switch(&desired_mod)
+    }
+
+    pub fn plan_default_settings(&mut self) -> Result<SwitchPlan, ModManagerError> {
+        let hm_mod = match self.default_settings_mod() {
+            // Before the first switch vaults it, the original music is still the installed one.
+            Err(ModManagerError::DefaultGameNotFound) => HotlineMod::from_music(
+                HotlineModName::from_directory(DEFAULT_GAME_DIRECTORY_NAME),
+                &self
+                    .original_music()?
+                    .ok_or(ModManagerError::DefaultGameNotFound)?,
+            ),
+            hm_mod => hm_mod?,
+        };
+        let receipt = self.install_receipt()?;
+
+        self.plan(&hm_mod, hm_mod.music().map(PlannedMusic::Wad), &receipt)
+    }
+
+    pub fn create_mod_folder(&mut self, name: &str) -> Result<&HotlineMod, ModManagerError> {
+        let new_mod = create_new_mod_folder(
+            name,
+            &self.all_mods,
+            self.configs.paths_config().mods_group_path(),
+        )?;
+        self.all_mods.0.push(HotlineMod::from_name(new_mod));
+
+        Ok(self.all_mods.0.last().expect("Mod pushed above"))
+    }
+
+    /// Merges the patchwads of a mod into one, keeping the originals in a backup folder inside the
+    /// mod.
+    pub fn merge_patchwads(
+        &mut self,
+        name: &str,
+        order: &[String],
+    ) -> Result<MergeReport, ModManagerError> {
+        let hm_mod = self.find_mod(name)?.clone();
+        let mod_dir = self.mod_dir(&hm_mod);
+
+        let report = merge_mod_patchwads(&hm_mod, &mod_dir, order)?;
+        self.reload_mod(&hm_mod, &mod_dir);
+
+        Ok(report)
+    }
+
+    /// Builds a music wad from the original music, or a mod's when `base` is given, with some
+    /// tracks taken from other mods or loose files, and makes it the music of the `target` mod,
+    /// which is created when it doesn't exist yet.
+    ///
+    /// Each override is written `TRACK=SOURCE`, where the source is a mod, a mod and one of its
+    /// tracks as `MOD:TRACK`, or an audio file.
+    pub fn compose_music(
+        &mut self,
+        target: &str,
+        base: Option<&str>,
+        overrides: &[String],
+    ) -> Result<ComposeReport, ModManagerError> {
+        let base = match base {
+            Some(name) => self.music_of(Some(name))?,
+            None => self
+                .default_settings_mod()?
+                .music()
+                .ok_or(ModManagerError::DefaultGameNotFound)?
+                .path()
+                .to_path_buf(),
+        };
+        let overrides = overrides
+            .iter()
+            .map(|track_override| self.track_override(track_override))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (hm_mod, created) = match self.all_mods.find(target) {
+            Some(hm_mod) => (hm_mod.clone(), false),
+            None => (self.create_mod_folder(target)?.clone(), true),
+        };
+        let mod_dir = self.mod_dir(&hm_mod);
+
+        let report = compose_mod_music(&hm_mod, &mod_dir, &base, &overrides);
+        if report.is_err() && created {
+            // Only the empty folders were created, so removing them can't lose anything.
+            let _ = fs::remove_dir(mod_dir.join(MUSIC_FOLDER_NAME));
+            let _ = fs::remove_dir(mod_dir.join(MODS_FOLDER_NAME));
+            let _ = fs::remove_dir(&mod_dir);
+            self.all_mods.0.retain(|listed| listed.name() != hm_mod.name());
+        }
+        let report = report?;
+        self.reload_mod(&hm_mod, &mod_dir);
+
+        Ok(report)
+    }
+
+    /// The tracks in a mod's music, or in the game's installed music when no mod is given.
+    pub fn music_tracks(&self, name: Option<&str>) -> Result<TrackList, ModManagerError> {
+        Ok(TrackList::read(&self.music_of(name)?)?)
+    }
+
+    /// The tracks a mod's music changes compared with the original music, or the installed music
+    /// when no mod is given.
+    pub fn diff_music(&self, name: Option<&str>) -> Result<TrackDiff, ModManagerError> {
+        let original = self
+            .music_vault
+            .as_ref()
+            .map(MusicVault::music)
+            .or_else(|| self.default_game_music().map(Music::path))
+            .ok_or(MusicTracksError::NoOriginalMusic)?;
+
+        Ok(TrackDiff::compare(original, &self.music_of(name)?)?)
+    }
+
+    /// Writes one track of a mod's music, or of the installed music when no mod is given, to
+    /// `output`.
+    pub fn export_music_track(
+        &self,
+        name: Option<&str>,
+        track: &str,
+        output: &Path,
+    ) -> Result<u64, ModManagerError> {
+        Ok(export_track(&self.music_of(name)?, track, output)?)
+    }
+
+    /// Changes one of the paths and saves it, so it's still there the next time the program runs.
+    pub fn set_configuration_path(
+        &mut self,
+        configuration_path: ConfigurationPath<WithPath>,
+    ) -> Result<(), ModManagerError> {
+        let group_changed = matches!(configuration_path, ConfigurationPath::Group(_));
+        self.configs.set_path(configuration_path)?;
+
+        // The mods are the ones of the new folder from now on.
+        if group_changed {
+            self.rescan_mods()?;
+            if self.mods_watcher.is_some() {
+                self.watch_mods()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn clear_configs(&self) -> Result<(), ModManagerError> {
+        Ok(self.configs.clear()?)
+    }
+
+    /// Switches the game files, the install receipt and the current mod as one step: if anything
+    /// fails, the previous music and patchwads are put back before returning the error.
+    fn install(
+        &mut self,
+        hm_mod: &HotlineMod,
+        music: Option<&Music>,
+    ) -> Result<SwitchReport, ModManagerError> {
+        let config_dir = self.configs.config_dir().clone();
+        let previous_receipt = self.install_receipt()?;
+        let plan = self.plan(hm_mod, music.map(PlannedMusic::Wad), &previous_receipt)?;
+        let mut transaction = SwitchTransaction::stage(&plan)?;
+
+        // Files the switch left untouched keep the method they were installed with before.
+        let method_of = |destination: &Path| {
+            transaction
+                .methods()
+                .find(|(staged, _)| *staged == destination)
+                .map_or_else(|| previous_receipt.method_of(destination), |(_, method)| method)
+        };
+        let installed_music = plan
+            .music()
+            .map(|music| (music.destination(), method_of(music.destination())));
+        let installed_mods = plan
+            .installed_mods()
+            .map(|copy| (copy.destination().to_path_buf(), method_of(copy.destination())))
+            .collect::<Vec<_>>();
+
+        let result = transaction
+            .apply()
+            .map_err(ModManagerError::from)
+            .and_then(|()| {
+                Ok(InstallReceipt::record(
+                    hm_mod,
+                    installed_music,
+                    &installed_mods,
+                    &mut self.hash_cache,
+                )
+                .map_err(InstallReceiptError::from)?
+                .write(&config_dir)?)
+            })
+            .and_then(|()| Ok(self.configs.set_current_mod(hm_mod.name().clone())?));
+
+        if let Err(err) = result {
+            let _ = previous_receipt.write(&config_dir);
+            return match transaction.rollback() {
+                Ok(()) => Err(err),
+                Err(rollback_err) => Err(ModManagerError::RollbackFailed(
+                    Box::new(err),
+                    rollback_err,
+                )),
+            };
+        }
+
+        transaction.commit();
+        self.hash_cache.save(&config_dir);
+
+        Ok(SwitchReport {
+            music_replaced: plan.music().is_some(),
+            modified: plan.modified().to_vec(),
+            unmanaged: plan.unmanaged().to_vec(),
+            conflicts: plan.conflicts().clone(),
+        })
+    }
+
+    fn plan(
+        &mut self,
+        hm_mod: &HotlineMod,
+        music: Option<PlannedMusic>,
+        receipt: &InstallReceipt,
+    ) -> Result<SwitchPlan, ModManagerError> {
+        let paths_config = self.configs.paths_config();
+        let survey = receipt
+            .survey(installed_patchwads(paths_config.mods_path())?)
+            .map_err(InstallReceiptError::from)?;
+
+        SwitchPlan::new(
+            paths_config.game_path(),
+            paths_config.mods_path(),
+            hm_mod,
+            music,
+            &survey,
+            self.configs.settings().install_strategy(),
+            &mut self.hash_cache,
+        )
+        .map_err(ModManagerError::Planning)
+    }
+
+    /// Copies the original game music into the vault the first time it's found, before a switch
+    /// can replace it, and uses it as the default game when there's no `hotline_miami_2` folder.
+    pub fn vault_original_music(&mut self) -> Result<(), ModManagerError> {
+        let config_dir = self.configs.config_dir().clone();
+        if self.music_vault.is_some() || MusicVault::exists(&config_dir) {
+            return Ok(());
+        }
+        let Some(original) = self.original_music()? else {
+            return Ok(());
+        };
+
+        let music_vault = MusicVault::capture(&config_dir, &original, &mut self.hash_cache)?;
+        self.hash_cache.save(&config_dir);
+
+        if self.default_game_music().is_none() {
+            self.default_game = Some(DefaultHotlineMod::from_vault(&music_vault));
+        }
+        self.music_vault = Some(music_vault);
+
+        Ok(())
+    }
+
+    /// The original game music: the one in the `hotline_miami_2` folder, or the game's own as
+    /// long as it isn't the music of a mod.
+    fn original_music(&mut self) -> Result<Option<PathBuf>, ModManagerError> {
+        if let Some(music) = self.default_game_music() {
+            return Ok(Some(music.path().to_path_buf()));
+        }
+
+        let installed = game_music_path(self.configs.paths_config().game_path());
+        if !fs::symlink_metadata(&installed).is_ok_and(|metadata| metadata.is_file()) {
+            return Ok(None);
+        }
+        if self.install_receipt()?.music().is_some() {
+            return Ok(None);
+        }
+        for music in self.all_mods.0.iter().filter_map(HotlineMod::music) {
+            if self
+                .hash_cache
+                .same_contents(music.path(), &installed)
+                .map_err(MusicVaultError::ReadingVault)?
+            {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(installed))
+    }
+
+    /// Reads the mod's folder again after its files changed.
+    fn reload_mod(&mut self, hm_mod: &HotlineMod, mod_dir: &Path) {
+        if let Some(reloaded) = HotlineMod::new(mod_dir) {
+            for listed in &mut self.all_mods.0 {
+                if listed.name() == hm_mod.name() {
+                    *listed = reloaded.clone();
+                }
+            }
+        }
+    }
+
+    /// Reads a `TRACK=SOURCE` override of [`ModManager::compose_music`].
+    fn track_override(&self, track_override: &str) -> Result<TrackOverride, ModManagerError> {
+        let (track, source) = track_override
+            .split_once('=')
+            .filter(|(track, source)| !track.is_empty() && !source.is_empty())
+            .ok_or_else(|| ComposeMusicError::InvalidOverride(track_override.into()))?;
+
+        let source = if let Some((name, source_track)) = source
+            .split_once(':')
+            .filter(|(name, _)| self.all_mods.find(name).is_some())
+        {
+            TrackSource::Wad {
+                path: self.music_of(Some(name))?,
+                track: source_track.to_string(),
+            }
+        } else if self.all_mods.find(source).is_some() {
+            TrackSource::Wad {
+                path: self.music_of(Some(source))?,
+                track: track.to_string(),
+            }
+        } else if Path::new(source).is_file() {
+            TrackSource::File(PathBuf::from(source))
+        } else {
+            return Err(ComposeMusicError::UnknownSource(source.into()).into());
+        };
+
+        Ok(TrackOverride::new(track.to_string(), source))
+    }
+
+    fn music_of(&self, name: Option<&str>) -> Result<PathBuf, ModManagerError> {
+        let Some(name) = name else {
+            return Ok(game_music_path(self.configs.paths_config().game_path()));
+        };
+        let hm_mod = self.find_mod(name)?;
+
+        Ok(hm_mod
+            .music()
+            .ok_or_else(|| MusicTracksError::NoMusic(hm_mod.name().to_string().into()))?
+            .path()
+            .to_path_buf())
+    }
+
+    /// The music the switch installs, composing it from the mod's single tracks if needed.
+    fn switch_music(&mut self, hm_mod: &HotlineMod) -> Result<Option<Music>, ModManagerError> {
+        match self.track_music(hm_mod)? {
+            Some(track_music) => Ok(Some(Music::new(track_music.compose()?))),
+            None => Ok(self.base_music(hm_mod)?.map(|music| Music::new(&music))),
+        }
+    }
+
+    /// The music the mod's `music_tracks` folder composes into, patched into the mod's music or
+    /// the default game's.
+    fn track_music(&mut self, hm_mod: &HotlineMod) -> Result<Option<TrackMusic>, ModManagerError> {
+        let Some(music_tracks) = hm_mod.music_tracks() else {
+            return Ok(None);
+        };
+        let base = self
+            .base_music(hm_mod)?
+            .ok_or(ModManagerError::DefaultGameNotFound)?;
+
+        Ok(Some(TrackMusic::plan(
+            hm_mod,
+            music_tracks,
+            &base,
+            self.configs.config_dir(),
+            &mut self.hash_cache,
+        )?))
+    }
+
+    /// The mod's music, or the default game's when it has none. Before the first switch vaults
+    /// it, the default game's is the original music still installed in the game folder.
+    fn base_music(&mut self, hm_mod: &HotlineMod) -> Result<Option<PathBuf>, ModManagerError> {
+        match hm_mod.music().or(self.default_game_music()) {
+            Some(music) => Ok(Some(music.path().to_path_buf())),
+            None => self.original_music(),
+        }
+    }
+
+    fn default_settings_mod(&mut self) -> Result<HotlineMod, ModManagerError> {
+        if let Some(default_game) = self
+            .default_game
+            .as_ref()
+            .filter(|default_game| default_game.music().is_some())
+        {
+            return Ok(default_game.hm_mod().clone());
+        }
+
+        // A vault that exists but couldn't be opened explains better what went wrong.
+        MusicVault::open(self.configs.config_dir(), &mut self.hash_cache)?;
+        Err(ModManagerError::DefaultGameNotFound)
+    }
+
+    /// The receipt of the last switch, or one claiming the current mod's files when the last
+    /// switch happened before receipts existed.
+    fn install_receipt(&self) -> Result<InstallReceipt, ModManagerError> {
+        if let Some(receipt) = InstallReceipt::read(self.configs.config_dir())? {
+            return Ok(receipt);
+        }
+
+        let mods_path = self.configs.paths_config().mods_path();
+        Ok(self
+            .current_mod()
+            .and_then(|current_mod| self.find_by_name(current_mod.name()))
+            .map_or_else(InstallReceipt::default, |hm_mod| {
+                InstallReceipt::adopt(mods_path, hm_mod)
+            }))
+    }
+
+    pub fn find_by_name(&self, name: &HotlineModName) -> Option<&HotlineMod> {
+        self.all_mods
+            .mods()
+            .iter()
+            .chain(self.default_game.as_deref())
+            .find(|hm_mod| hm_mod.name() == name)
+    }
+
+    fn default_game_music(&self) -> Option<&Music> {
+        self.default_game
+            .as_ref()
+            .and_then(|default_mod| default_mod.music())
+    }
+}
+
+/// What a mod switch changed in the game folders.
+#[derive(Debug, Clone)]
+pub struct SwitchReport {
+    music_replaced: bool,
+    modified: Vec<PathBuf>,
+    unmanaged: Vec<PathBuf>,
+    conflicts: ConflictReport,
+}
+
+impl SwitchReport {
+    /// False when neither the mod nor the default game had music to install.
+    pub fn music_replaced(&self) -> bool {
+        self.music_replaced
+    }
+
+    /// Files the manager installed before, left in place because they were changed since.
+    pub fn modified(&self) -> &[PathBuf] {
+        &self.modified
+    }
+
+    /// Patchwads the manager didn't install, left in place.
+    pub fn unmanaged(&self) -> &[PathBuf] {
+        &self.unmanaged
+    }
+
+    /// Assets more than one of the installed patchwads provides.
+    pub fn conflicts(&self) -> &ConflictReport {
+        &self.conflicts
+    }
+
+    /// Everything the user should know about the switch.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if !self.music_replaced {
+            warnings.push(ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING.to_string());
+        }
+        warnings.extend(self.modified.iter().map(|path| {
+            format!(
+                "Left {} in your mods folder because it was changed after the manager installed it.",
+                display_file_name(path)
+            )
+        }));
+        warnings.extend(self.unmanaged.iter().map(|path| {
+            format!(
+                "Left {} in your mods folder because it wasn't installed by the manager.",
+                display_file_name(path)
+            )
+        }));
+        warnings.extend(
+            self.conflicts
+                .conflicts()
+                .iter()
+                .map(|conflict| format!("Conflict: {conflict}")),
+        );
+
+        warnings
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ModManagerError {
+    #[error("{0}")]
+    Configs(#[from] ConfigsError),
+    #[error("Error reading the folder where you keep your mods. Error: {0}")]
+    ListingMods(io::Error),
+    #[error("There's no mod called {0} in your mods directory.")]
+    ModNotFound(Box<str>),
+    #[error("{ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING}")]
+    DefaultGameNotFound,
+    #[error("Error working out what the switch has to do. Error: {0}")]
+    Planning(io::Error),
+    #[error("{0}")]
+    Switch(#[from] SwitchError),
+    #[error("{0}")]
+    ReplaceMods(#[from] ReplaceModError),
+    #[error("{0}")]
+    InstallReceipt(#[from] InstallReceiptError),
+    #[error("{0}")]
+    Fingerprint(#[from] FingerprintError),
+    #[error("{0}")]
+    MusicVault(#[from] MusicVaultError),
+    #[error("{0}")]
+    Vanilla(#[from] VanillaError),
+    #[error("{0} Then, putting the previous files back failed too. {1}")]
+    RollbackFailed(Box<ModManagerError>, SwitchError),
+    #[error("{0}")]
+    CreateNewModFolder(#[from] CreateNewModFolderError),
+    #[error("{0}")]
+    MergePatchwads(#[from] MergePatchwadsError),
+    #[error("{0}")]
+    MusicTracks(#[from] MusicTracksError),
+    #[error("{0}")]
+    ComposeMusic(#[from] ComposeMusicError),
+    #[error("{0}")]
+    ModManifest(#[from] ModManifestError),
+    #[error("{0}")]
+    ModsWatcher(#[from] ModsWatcherError),
+}
+
+pub struct DefaultHotlineMod(HotlineMod);
+
+impl DefaultHotlineMod {
+    fn from_vault(music_vault: &MusicVault) -> Self {
+        DefaultHotlineMod(HotlineMod::from_music(
+            HotlineModName::from_directory(DEFAULT_GAME_DIRECTORY_NAME),
+            music_vault.music(),
+        ))
+    }
+
+    pub fn hm_mod(&self) -> &HotlineMod {
+        self
+    }
+}
+
+impl Deref for DefaultHotlineMod {
+    type Target = HotlineMod;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+fn display_file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+}
+
+/// Lists the mods group folder, taking the `hotline_miami_2` folder out of the mods.
+fn list_library(
+    mods_group_path: &ModsGroupPath,
+) -> Result<(AllMods, Option<HotlineMod>), ModManagerError> {
+    let mut all_mods = list_mods(mods_group_path).map_err(ModManagerError::ListingMods)?;
+    let default_game_index = all_mods.0.iter().position(|hm_mod| {
+        hm_mod.name().directory_name().to_string_lossy() == DEFAULT_GAME_DIRECTORY_NAME
+    });
+
+    let default_game_folder = default_game_index.map(|index| all_mods.0.remove(index));
+
+    Ok((all_mods, default_game_folder))
+}
+
+/// A `hotline_miami_2` folder with music wins over the vault, since the user set it up.
+fn default_game(
+    folder: Option<HotlineMod>,
+    music_vault: Option<&MusicVault>,
+) -> Option<DefaultHotlineMod> {
+    match (folder, music_vault) {
+        (Some(folder), _) if folder.music().is_some() => Some(DefaultHotlineMod(folder)),
+        (_, Some(music_vault)) => Some(DefaultHotlineMod::from_vault(music_vault)),
+        (folder, None) => folder.map(DefaultHotlineMod),
+    }
+}
+
+fn list_mods(mods_path: &ModsGroupPath) -> io::Result<AllMods> {
+    let vec = fs::read_dir(mods_path.path())?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| HotlineMod::new(&path))
+        .collect();
+
+    Ok(AllMods::new(vec))
+}
+
+pub struct AllMods(Vec<HotlineMod>);
+
+impl AllMods {
+    fn new(mods: Vec<HotlineMod>) -> Self {
+        AllMods(mods)
+    }
+
+    pub fn mods(&self) -> &[HotlineMod] {
+        &self.0
+    }
+
+    pub fn find(&self, name: &str) -> Option<&HotlineMod> {
+        self.0.iter().find(|hm_mod| {
+            hm_mod.name().directory_name().to_string_lossy() == name
+                || hm_mod.name().formatted_name().eq_ignore_ascii_case(name)
+        })
+    }
+}
+
+const DEFAULT_GAME_DIRECTORY_NAME: &str = "hotline_miami_2";
+
+pub const ORIGINAL_GAME_SETTINGS_NOT_FOUND_WARNING: &str = "Attention: The original Hotline Miami 2 music couldn't be found. The program keeps a copy of it the first time it runs, but the game's music was already replaced by a mod. Verify the game files in Steam so the original music is back, or create a folder named 'hotline_miami_2' with the original game's music in it. Without it, the program may not behave as expected. For more information, visit the project's GitHub (https://github.com/cardosoOReis/hotline_miami_mod_manager.git).";
@@ -0,0 +1,117 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const MOD_MANIFEST_FILE_NAME: &str = "mod.toml";
+
+/// What a mod's `mod.toml` says about it. Every field is optional.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ModManifest {
+    name: Option<String>,
+    author: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    source_url: Option<String>,
+    workshop_id: Option<u64>,
+    notes: Option<String>,
+}
+
+impl ModManifest {
+    /// Reads the `mod.toml` inside the mod folder, returning `None` when there's none.
+    pub fn read(mod_path: &Path) -> Result<Option<Self>, ModManifestError> {
+        match fs::read_to_string(mod_path.join(MOD_MANIFEST_FILE_NAME)) {
+            Ok(contents) => Ok(Some(toml::from_str(&contents)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(ModManifestError::FileLoadingError(err)),
+        }
+    }
+
+    /// The name to show instead of the one made from the folder name.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref().filter(|name| !name.trim().is_empty())
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Where the mod can be downloaded.
+    pub fn source_url(&self) -> Option<&str> {
+        self.source_url.as_deref()
+    }
+
+    pub fn workshop_id(&self) -> Option<u64> {
+        self.workshop_id
+    }
+
+    /// The Steam Workshop page of the mod.
+    pub fn workshop_url(&self) -> Option<String> {
+        self.workshop_id.map(|workshop_id| {
+            format!("https://steamcommunity.com/sharedfiles/filedetails/?id={workshop_id}")
+        })
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// The version and author on one line, like `v1.2 by Someone`, for lists of mods.
+    pub fn byline(&self) -> Option<String> {
+        let version = self.version.as_deref().map(|version| format!("v{version}"));
+        let author = self.author.as_deref().map(|author| format!("by {author}"));
+
+        match (version, author) {
+            (Some(version), Some(author)) => Some(format!("{version} {author}")),
+            (version, author) => version.or(author),
+        }
+    }
+}
+
+/// Every field that's set, one per line.
+impl fmt::Display for ModManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            ("Author", self.author.clone()),
+            ("Version", self.version.clone()),
+            ("Description", self.description.clone()),
+            (
+                "Tags",
+                Some(self.tags.join(", ")).filter(|tags| !tags.is_empty()),
+            ),
+            ("Source", self.source_url.clone()),
+            ("Steam Workshop", self.workshop_url()),
+            ("Notes", self.notes.clone()),
+        ];
+
+        for (label, value) in fields {
+            if let Some(value) = value {
+                writeln!(f, "  {label}: {value}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ModManifestError {
+    #[error("Error reading the mod.toml file. Error: {0}")]
+    FileLoadingError(io::Error),
+    #[error("The mod.toml file is invalid. Error: {0}")]
+    InvalidFileContent(#[from] toml::de::Error),
+}
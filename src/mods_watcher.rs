@@ -0,0 +1,48 @@
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver},
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use thiserror::Error;
+
+/// Watches the mods group folder for mods being added, removed, renamed or changed.
+pub struct ModsWatcher {
+    // Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl ModsWatcher {
+    pub fn new(mods_group_path: &Path) -> Result<Self, ModsWatcherError> {
+        let watching_error = |err| ModsWatcherError::Watching(err, mods_group_path.into());
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watching_error)?;
+        watcher
+            .watch(mods_group_path, RecursiveMode::Recursive)
+            .map_err(watching_error)?;
+
+        Ok(ModsWatcher {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Whether anything changed since the last call, without waiting. Files being read, like
+    /// when a switch copies them, don't count.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            changed |= event.is_ok_and(|event| !matches!(event.kind, EventKind::Access(_)));
+        }
+
+        changed
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ModsWatcherError {
+    #[error("Error watching the folder {} for changes. Error: {0}", .1.display())]
+    Watching(notify::Error, Box<Path>),
+}
@@ -0,0 +1,473 @@
+use std::{
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use indicatif::HumanBytes;
+use thiserror::Error;
+
+use crate::{
+    file_hash::FileHash,
+    wad::{validate::validate_wad, Wad, WadEntry, WadError},
+};
+
+/// The tracks inside a music wad.
+#[derive(Debug, Clone)]
+pub struct TrackList {
+    music: PathBuf,
+    tracks: Vec<MusicTrack>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MusicTrack {
+    name: String,
+    size: u64,
+    format: TrackFormat,
+}
+
+/// The audio format of a track, worked out from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackFormat {
+    Ogg,
+    Wav,
+    Mp3,
+    Flac,
+    Unknown,
+}
+
+impl TrackList {
+    /// Reads the file table of the music wad, and the first bytes of every track to find its
+    /// format.
+    pub fn read(music: &Path) -> Result<Self, MusicTracksError> {
+        let wad = validate_wad(music)?;
+        let mut file = File::open(music).map_err(WadError::from)?;
+
+        let tracks = wad
+            .entries()
+            .iter()
+            .map(|entry| {
+                Ok(MusicTrack {
+                    name: entry.name().to_string(),
+                    size: entry.size(),
+                    format: TrackFormat::detect(&wad, &mut file, entry)?,
+                })
+            })
+            .collect::<Result<_, MusicTracksError>>()?;
+
+        Ok(TrackList {
+            music: music.to_path_buf(),
+            tracks,
+        })
+    }
+
+    pub fn music(&self) -> &Path {
+        &self.music
+    }
+
+    pub fn tracks(&self) -> &[MusicTrack] {
+        &self.tracks
+    }
+}
+
+impl MusicTrack {
+    /// The path of the track inside the wad, like `Music/Hotline.ogg`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn format(&self) -> TrackFormat {
+        self.format
+    }
+}
+
+impl TrackFormat {
+    fn detect(wad: &Wad, file: &mut File, entry: &WadEntry) -> Result<Self, MusicTracksError> {
+        let mut magic = Vec::with_capacity(12);
+        wad.entry_reader(file, entry)?
+            .take(12)
+            .read_to_end(&mut magic)
+            .map_err(WadError::from)?;
+
+        let format = match magic.as_slice() {
+            [b'O', b'g', b'g', b'S', ..] => TrackFormat::Ogg,
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => TrackFormat::Wav,
+            [b'f', b'L', b'a', b'C', ..] => TrackFormat::Flac,
+            [b'I', b'D', b'3', ..] => TrackFormat::Mp3,
+            [0xFF, second, ..] if second & 0xE0 == 0xE0 => TrackFormat::Mp3,
+            _ => TrackFormat::Unknown,
+        };
+
+        Ok(format)
+    }
+}
+
+impl fmt::Display for TrackList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} tracks in {}:",
+            self.tracks.len(),
+            self.music.display()
+        )?;
+        for track in &self.tracks {
+            writeln!(
+                f,
+                "  {} ({}, {})",
+                track.name,
+                track.format,
+                HumanBytes(track.size)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for TrackFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self {
+            TrackFormat::Ogg => "ogg",
+            TrackFormat::Wav => "wav",
+            TrackFormat::Mp3 => "mp3",
+            TrackFormat::Flac => "flac",
+            TrackFormat::Unknown => "unknown format",
+        };
+
+        write!(f, "{format}")
+    }
+}
+
+/// The tracks a music wad changes compared with the original music.
+#[derive(Debug, Clone)]
+pub struct TrackDiff {
+    music: PathBuf,
+    changes: Vec<TrackChange>,
+    unchanged: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackChange {
+    name: String,
+    kind: TrackChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackChangeKind {
+    Replaced { size: u64, original_size: u64 },
+    Added { size: u64 },
+    /// The original has the track and the music doesn't, so the game can't play it.
+    Removed { original_size: u64 },
+}
+
+impl TrackDiff {
+    /// Compares every track of `music` with the one of the same name in `original`. Tracks of the
+    /// same size are hashed, so only those with different bytes count as replaced.
+    pub fn compare(original: &Path, music: &Path) -> Result<Self, MusicTracksError> {
+        let original_wad = validate_wad(original)?;
+        let music_wad = validate_wad(music)?;
+        let mut original_file = File::open(original).map_err(WadError::from)?;
+        let mut music_file = File::open(music).map_err(WadError::from)?;
+
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+        for entry in music_wad.entries() {
+            let kind = match original_wad.find(entry.name()) {
+                None => Some(TrackChangeKind::Added { size: entry.size() }),
+                Some(original_entry)
+                    if original_entry.size() != entry.size()
+                        || hash_entry(&original_wad, &mut original_file, original_entry)?
+                            != hash_entry(&music_wad, &mut music_file, entry)? =>
+                {
+                    Some(TrackChangeKind::Replaced {
+                        size: entry.size(),
+                        original_size: original_entry.size(),
+                    })
+                }
+                Some(_) => None,
+            };
+
+            match kind {
+                Some(kind) => changes.push(TrackChange {
+                    name: entry.name().to_string(),
+                    kind,
+                }),
+                None => unchanged += 1,
+            }
+        }
+
+        for original_entry in original_wad.entries() {
+            if music_wad.find(original_entry.name()).is_none() {
+                changes.push(TrackChange {
+                    name: original_entry.name().to_string(),
+                    kind: TrackChangeKind::Removed {
+                        original_size: original_entry.size(),
+                    },
+                });
+            }
+        }
+
+        Ok(TrackDiff {
+            music: music.to_path_buf(),
+            changes,
+            unchanged,
+        })
+    }
+
+    pub fn changes(&self) -> &[TrackChange] {
+        &self.changes
+    }
+
+    /// How many tracks are exactly the same as in the original music.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl TrackChange {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> TrackChangeKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for TrackDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Tracks of {} compared with the original music:",
+            self.music.display()
+        )?;
+        if self.changes.is_empty() {
+            writeln!(f, "  Every track is the original one")?;
+        }
+        for change in &self.changes {
+            writeln!(f, "  {change}")?;
+        }
+        writeln!(f, "  Unchanged tracks: {}", self.unchanged)
+    }
+}
+
+impl fmt::Display for TrackChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            TrackChangeKind::Replaced {
+                size,
+                original_size,
+            } => write!(
+                f,
+                "Replaced: {} ({}, the original is {})",
+                self.name,
+                HumanBytes(size),
+                HumanBytes(original_size)
+            ),
+            TrackChangeKind::Added { size } => {
+                write!(f, "Added: {} ({})", self.name, HumanBytes(size))
+            }
+            TrackChangeKind::Removed { original_size } => write!(
+                f,
+                "Removed: {} ({} in the original)",
+                self.name,
+                HumanBytes(original_size)
+            ),
+        }
+    }
+}
+
+/// Writes one track of the music wad to `output`, which must not exist yet, returning its size.
+pub fn export_track(music: &Path, track: &str, output: &Path) -> Result<u64, MusicTracksError> {
+    let wad = validate_wad(music)?;
+    let entry = wad
+        .find(track)
+        .ok_or_else(|| MusicTracksError::TrackNotFound(track.into(), music.into()))?;
+
+    let exporting_error = |err| MusicTracksError::Exporting(err, output.into());
+    let mut file = File::open(music).map_err(WadError::from)?;
+    let mut writer = BufWriter::new(
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(output)
+            .map_err(exporting_error)?,
+    );
+    let result = wad
+        .copy_entry(&mut file, entry, &mut writer)
+        .map_err(MusicTracksError::from)
+        .and_then(|()| writer.flush().map_err(exporting_error));
+    if let Err(err) = result {
+        drop(writer);
+        let _ = fs::remove_file(output);
+        return Err(err);
+    }
+
+    Ok(entry.size())
+}
+
+fn hash_entry(wad: &Wad, file: &mut File, entry: &WadEntry) -> Result<FileHash, MusicTracksError> {
+    FileHash::of_reader(wad.entry_reader(file, entry)?)
+        .map_err(|err| MusicTracksError::Wad(err.into()))
+}
+
+#[derive(Error, Debug)]
+pub enum MusicTracksError {
+    #[error("{0}")]
+    Wad(#[from] WadError),
+    #[error("{0} has no music.")]
+    NoMusic(Box<str>),
+    #[error("The original music was never vaulted, so there's nothing to compare with.")]
+    NoOriginalMusic,
+    #[error("There's no track called {0} in {}.", .1.display())]
+    TrackNotFound(Box<str>, Box<Path>),
+    #[error("Error writing the track to {}. Error: {0}", .1.display())]
+    Exporting(io::Error, Box<Path>),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wad::WAD_VERSION;
+
+    use super::*;
+
+    /// Writes a wad at `path` holding the given tracks.
+    fn write_music(path: &Path, tracks: &[(&str, &[u8])]) {
+        let mut offset = 0;
+        let entries = tracks
+            .iter()
+            .map(|(name, data)| {
+                let entry = WadEntry::new((*name).to_string(), data.len() as u64, offset);
+                offset += data.len() as u64;
+                entry
+            })
+            .collect();
+        let wad = Wad::new(WAD_VERSION, Vec::new(), entries, Vec::new());
+
+        let mut bytes = Vec::new();
+        wad.write_tables(&mut bytes).unwrap();
+        for (_, data) in tracks {
+            bytes.extend(*data);
+        }
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn detects_the_format_from_the_first_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let music = dir.path().join("music.wad");
+        write_music(
+            &music,
+            &[
+                ("Music/Ogg.ogg", b"OggS\0\x02"),
+                ("Music/Wav.wav", b"RIFF\x24\0\0\0WAVEfmt "),
+                ("Music/Flac.flac", b"fLaC\0\0\0\x22"),
+                ("Music/Tagged.mp3", b"ID3\x04\0"),
+                ("Music/Frame.mp3", b"\xFF\xFB\x90\x64"),
+                ("Music/Riff.avi", b"RIFF\x24\0\0\0AVI LIST"),
+                ("Music/Short.ogg", b"Og"),
+                ("Music/Empty.ogg", b""),
+            ],
+        );
+
+        let formats = TrackList::read(&music)
+            .unwrap()
+            .tracks()
+            .iter()
+            .map(MusicTrack::format)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            formats,
+            [
+                TrackFormat::Ogg,
+                TrackFormat::Wav,
+                TrackFormat::Flac,
+                TrackFormat::Mp3,
+                TrackFormat::Mp3,
+                TrackFormat::Unknown,
+                TrackFormat::Unknown,
+                TrackFormat::Unknown,
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_the_tracks_that_differ_from_the_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.wad");
+        let music = dir.path().join("music.wad");
+        write_music(
+            &original,
+            &[
+                ("Music/Same.ogg", b"same"),
+                ("Music/Edited.ogg", b"edit"),
+                ("Music/Longer.ogg", b"long"),
+                ("Music/Credits.ogg", b"credits"),
+            ],
+        );
+        write_music(
+            &music,
+            &[
+                ("Music/Same.ogg", b"same"),
+                ("Music/Edited.ogg", b"EDIT"),
+                ("Music/Longer.ogg", b"longer"),
+                ("Music/Bonus.ogg", b"bonus"),
+            ],
+        );
+
+        let diff = TrackDiff::compare(&original, &music).unwrap();
+
+        let changes = diff
+            .changes()
+            .iter()
+            .map(|change| (change.name(), change.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                (
+                    "Music/Edited.ogg",
+                    TrackChangeKind::Replaced {
+                        size: 4,
+                        original_size: 4
+                    }
+                ),
+                (
+                    "Music/Longer.ogg",
+                    TrackChangeKind::Replaced {
+                        size: 6,
+                        original_size: 4
+                    }
+                ),
+                ("Music/Bonus.ogg", TrackChangeKind::Added { size: 5 }),
+                (
+                    "Music/Credits.ogg",
+                    TrackChangeKind::Removed { original_size: 7 }
+                ),
+            ]
+        );
+        assert_eq!(diff.unchanged(), 1);
+    }
+
+    #[test]
+    fn the_original_music_has_no_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.wad");
+        write_music(&original, &[("Music/Same.ogg", b"same")]);
+
+        let diff = TrackDiff::compare(&original, &original).unwrap();
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.unchanged(), 1);
+    }
+}
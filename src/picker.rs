@@ -0,0 +1,341 @@
+use std::io::{self, IsTerminal, Write};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::Stylize,
+    terminal::{self, Clear, ClearType},
+};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use inquire::{error::InquireResult, InquireError};
+
+const PAGE_SIZE: usize = 20;
+
+/// A list to choose from by typing part of an option, showing details about the highlighted one,
+/// with several orders to switch between.
+#[derive(Debug, Clone)]
+pub struct Picker {
+    message: String,
+    options: Vec<PickerOption>,
+    orders: Vec<PickerOrder>,
+    starting_order: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PickerOption {
+    label: String,
+    keywords: String,
+    detail: String,
+}
+
+/// One way to order the options, as their indices in that order.
+#[derive(Debug, Clone)]
+pub struct PickerOrder {
+    name: String,
+    indices: Vec<usize>,
+}
+
+impl Picker {
+    pub fn new(message: impl Into<String>, options: Vec<PickerOption>) -> Self {
+        Picker {
+            message: message.into(),
+            options,
+            orders: Vec::new(),
+            starting_order: 0,
+        }
+    }
+
+    /// Adds an order the user can switch to with Tab. Without any, the options keep theirs.
+    pub fn with_order(mut self, order: PickerOrder) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    pub fn with_starting_order(mut self, starting_order: usize) -> Self {
+        self.starting_order = starting_order;
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn options(&self) -> &[PickerOption] {
+        &self.options
+    }
+
+    pub fn orders(&self) -> &[PickerOrder] {
+        &self.orders
+    }
+
+    /// The indices of the options that match the search, best matches first. Options that match
+    /// equally keep the order.
+    pub fn matches(&self, order: usize, search: &str) -> Vec<usize> {
+        let ordered = self.orders.get(order).map_or_else(
+            || (0..self.options.len()).collect(),
+            |order| order.indices.clone(),
+        );
+        if search.trim().is_empty() {
+            return ordered;
+        }
+
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored = ordered
+            .into_iter()
+            .filter_map(|index| {
+                let option = &self.options[index];
+                let text = format!("{} {}", option.label, option.keywords);
+                matcher
+                    .fuzzy_match(&text, search.trim())
+                    .map(|score| (score, index))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|(score, _)| -score);
+
+        scored.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// Shows the picker in the terminal, returning the index of the chosen option.
+    pub fn prompt(&self) -> InquireResult<usize> {
+        if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+            return Err(InquireError::NotTTY);
+        }
+
+        let chosen = {
+            let _raw_mode = RawMode::enable()?;
+            PickerState::new(self).run()?
+        };
+        eprintln!(
+            "{} {} {}",
+            "?".green(),
+            self.message,
+            self.options[chosen].label.as_str().cyan()
+        );
+
+        Ok(chosen)
+    }
+}
+
+impl PickerOption {
+    /// `keywords` are searched too without being shown, and `detail` is shown under the list
+    /// while the option is highlighted.
+    pub fn new(
+        label: impl Into<String>,
+        keywords: impl Into<String>,
+        detail: impl Into<String>,
+    ) -> Self {
+        PickerOption {
+            label: label.into(),
+            keywords: keywords.into(),
+            detail: detail.into(),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+}
+
+impl PickerOrder {
+    pub fn new(name: impl Into<String>, indices: Vec<usize>) -> Self {
+        PickerOrder {
+            name: name.into(),
+            indices,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+struct PickerState<'a> {
+    picker: &'a Picker,
+    search: String,
+    order: usize,
+    matches: Vec<usize>,
+    cursor: usize,
+    scroll: usize,
+    drawn_lines: u16,
+}
+
+impl<'a> PickerState<'a> {
+    fn new(picker: &'a Picker) -> Self {
+        let order = picker
+            .starting_order
+            .min(picker.orders.len().saturating_sub(1));
+        PickerState {
+            picker,
+            search: String::new(),
+            order,
+            matches: picker.matches(order, ""),
+            cursor: 0,
+            scroll: 0,
+            drawn_lines: 0,
+        }
+    }
+
+    fn run(&mut self) -> InquireResult<usize> {
+        let mut stderr = io::stderr();
+
+        let chosen = loop {
+            self.draw(&mut stderr)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if let Some(result) = self.handle(key) {
+                break result;
+            }
+        };
+
+        self.clear(&mut stderr)?;
+        stderr.flush()?;
+        chosen
+    }
+
+    /// Returns the result once the user chose an option or left.
+    fn handle(&mut self, key: KeyEvent) -> Option<InquireResult<usize>> {
+        let count = self.matches.len();
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(Err(InquireError::OperationInterrupted));
+            }
+            KeyCode::Esc => return Some(Err(InquireError::OperationCanceled)),
+            KeyCode::Enter => {
+                if let Some(index) = self.matches.get(self.cursor) {
+                    return Some(Ok(*index));
+                }
+            }
+            KeyCode::Up if count > 0 => self.cursor = (self.cursor + count - 1) % count,
+            KeyCode::Down if count > 0 => self.cursor = (self.cursor + 1) % count,
+            KeyCode::PageUp => self.cursor = self.cursor.saturating_sub(PAGE_SIZE),
+            KeyCode::PageDown if count > 0 => {
+                self.cursor = (self.cursor + PAGE_SIZE).min(count - 1);
+            }
+            KeyCode::Tab if !self.picker.orders.is_empty() => {
+                self.order = (self.order + 1) % self.picker.orders.len();
+                self.refresh();
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+                self.refresh();
+            }
+            KeyCode::Char(character) => {
+                self.search.push(character);
+                self.refresh();
+            }
+            _ => {}
+        }
+
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + PAGE_SIZE {
+            self.scroll = self.cursor + 1 - PAGE_SIZE;
+        }
+
+        None
+    }
+
+    fn refresh(&mut self) {
+        self.matches = self.picker.matches(self.order, &self.search);
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.clear(out)?;
+
+        let width = terminal::size().map_or(80, |(columns, _)| usize::from(columns).max(10)) - 1;
+        let fit = |line: &str| line.chars().take(width).collect::<String>();
+
+        let mut lines = vec![format!(
+            "{} {} {}",
+            "?".green(),
+            fit(&self.picker.message),
+            self.search
+        )];
+        for (position, index) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(PAGE_SIZE)
+        {
+            let label = fit(&format!("  {}", self.picker.options[*index].label));
+            lines.push(if position == self.cursor {
+                format!(">{}", &label[1..]).cyan().to_string()
+            } else {
+                label
+            });
+        }
+        match self.matches.get(self.cursor) {
+            Some(index) => lines.push(
+                fit(&format!("  {}", self.picker.options[*index].detail))
+                    .dark_grey()
+                    .to_string(),
+            ),
+            None => lines.push("  Nothing matches your search".dark_grey().to_string()),
+        }
+        lines.push(fit(&self.help()).dark_grey().to_string());
+
+        write!(out, "{}", lines.join("\r\n"))?;
+        self.drawn_lines = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+        out.flush()
+    }
+
+    fn help(&self) -> String {
+        let orders = &self.picker.orders;
+        let sorting = if orders.len() > 1 {
+            format!(
+                ", sorted by {}, tab to sort by {}",
+                orders[self.order].name,
+                orders[(self.order + 1) % orders.len()].name
+            )
+        } else {
+            String::new()
+        };
+
+        format!("[type to search, ↑↓ to move, enter to choose, esc to cancel{sorting}]")
+    }
+
+    /// Erases what the last draw printed, leaving the cursor where it started.
+    fn clear(&mut self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, cursor::MoveToColumn(0))?;
+        if self.drawn_lines > 1 {
+            queue!(out, cursor::MoveUp(self.drawn_lines - 1))?;
+        }
+        queue!(out, Clear(ClearType::FromCursorDown))?;
+        self.drawn_lines = 0;
+
+        Ok(())
+    }
+}
+
+/// Puts the terminal back to normal when dropped, even after an error.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        queue!(io::stderr(), cursor::Hide)?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = queue!(io::stderr(), cursor::Show);
+        let _ = io::stderr().flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
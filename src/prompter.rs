@@ -0,0 +1,156 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use inquire::{error::InquireResult, Confirm, InquireError, Select, Text};
+
+use crate::picker::Picker;
+
+/// Everything the interactive flows need to ask the user.
+pub trait Prompter {
+    fn text(&mut self, message: &str) -> InquireResult<String>;
+    /// Returns the index of the chosen option.
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        help_message: Option<&str>,
+    ) -> InquireResult<usize>;
+    /// Asks a yes or no question, answering `default` when the user just presses Enter.
+    fn confirm(&mut self, message: &str, default: bool) -> InquireResult<bool>;
+    /// Like [`Prompter::select`] with search, details and orders. Returns the index of the chosen
+    /// option.
+    fn pick(&mut self, picker: &Picker) -> InquireResult<usize>;
+    /// Tells the user what was wrong with their last answer, before asking again.
+    fn warn(&mut self, warning: &dyn Display);
+}
+
+/// Asks the user through the terminal.
+#[derive(Debug, Default)]
+pub struct InquirePrompter;
+
+impl Prompter for InquirePrompter {
+    fn text(&mut self, message: &str) -> InquireResult<String> {
+        Text::new(message).prompt()
+    }
+
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        help_message: Option<&str>,
+    ) -> InquireResult<usize> {
+        let mut select = Select::new(message, options.to_vec()).with_page_size(20);
+        if let Some(help_message) = help_message {
+            select = select.with_help_message(help_message);
+        }
+
+        select.raw_prompt().map(|option| option.index)
+    }
+
+    fn confirm(&mut self, message: &str, default: bool) -> InquireResult<bool> {
+        Confirm::new(message).with_default(default).prompt()
+    }
+
+    fn pick(&mut self, picker: &Picker) -> InquireResult<usize> {
+        picker.prompt()
+    }
+
+    fn warn(&mut self, warning: &dyn Display) {
+        println!("{warning}");
+    }
+}
+
+/// Replays a fixed list of answers, so the interactive flows can run without a terminal.
+///
+/// Once every answer is consumed, further prompts act as if the user pressed Ctrl+C.
+#[derive(Debug, Default)]
+pub struct ScriptedPrompter {
+    answers: VecDeque<ScriptedAnswer>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ScriptedAnswer {
+    /// Typed text for a text prompt.
+    Text(String),
+    /// The label of the option to choose in a select prompt or a picker.
+    Select(String),
+    /// The answer to a yes or no question.
+    Confirm(bool),
+    /// The user pressed ESC.
+    Cancel,
+    /// The user pressed Ctrl+C.
+    Interrupt,
+}
+
+impl ScriptedPrompter {
+    pub fn new(answers: impl IntoIterator<Item = ScriptedAnswer>) -> Self {
+        ScriptedPrompter {
+            answers: answers.into_iter().collect(),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.answers.len()
+    }
+
+    fn next_answer(&mut self) -> InquireResult<ScriptedAnswer> {
+        match self.answers.pop_front() {
+            Some(ScriptedAnswer::Cancel) => Err(InquireError::OperationCanceled),
+            Some(ScriptedAnswer::Interrupt) | None => Err(InquireError::OperationInterrupted),
+            Some(answer) => Ok(answer),
+        }
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn text(&mut self, message: &str) -> InquireResult<String> {
+        match self.next_answer()? {
+            ScriptedAnswer::Text(text) => Ok(text),
+            answer => Err(unexpected_answer(message, &answer)),
+        }
+    }
+
+    fn select(
+        &mut self,
+        message: &str,
+        options: &[String],
+        _help_message: Option<&str>,
+    ) -> InquireResult<usize> {
+        match self.next_answer()? {
+            ScriptedAnswer::Select(label) => options
+                .iter()
+                .position(|option| *option == label)
+                .ok_or_else(|| {
+                    InquireError::InvalidConfiguration(format!(
+                        "Scripted option {label} is not one of the options for: {message}"
+                    ))
+                }),
+            answer => Err(unexpected_answer(message, &answer)),
+        }
+    }
+
+    fn confirm(&mut self, message: &str, _default: bool) -> InquireResult<bool> {
+        match self.next_answer()? {
+            ScriptedAnswer::Confirm(answer) => Ok(answer),
+            answer => Err(unexpected_answer(message, &answer)),
+        }
+    }
+
+    fn pick(&mut self, picker: &Picker) -> InquireResult<usize> {
+        let labels = picker
+            .options()
+            .iter()
+            .map(|option| option.label().to_string())
+            .collect::<Vec<_>>();
+
+        self.select(picker.message(), &labels, None)
+    }
+
+    /// The next answer is the retry, so there's nothing to show.
+    fn warn(&mut self, _warning: &dyn Display) {}
+}
+
+fn unexpected_answer(message: &str, answer: &ScriptedAnswer) -> InquireError {
+    InquireError::InvalidConfiguration(format!(
+        "Scripted answer {answer:?} does not fit the prompt: {message}"
+    ))
+}
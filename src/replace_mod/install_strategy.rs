@@ -0,0 +1,75 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// How the files of a mod get into the game folders.
+///
+/// Every method other than [`InstallMethod::Copy`] falls back to a copy when it isn't possible,
+/// like a hardlink across drives, so a switch never fails because of the chosen method.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMethod {
+    /// A full copy of the bytes.
+    #[default]
+    Copy,
+    /// A second name for the same file, only on the same drive. Takes no extra space.
+    Hardlink,
+    /// A link pointing at the file in your mods folder, which has to stay where it is.
+    Symlink,
+    /// A copy that shares the bytes until one of them changes, on filesystems that support it
+    /// like Btrfs, XFS or APFS.
+    Reflink,
+}
+
+impl InstallMethod {
+    /// Places `source` at `target` with this method, returning `None` when the caller has to copy
+    /// it instead.
+    pub fn link(self, source: &Path, target: &Path) -> Option<InstallMethod> {
+        let result = match self {
+            InstallMethod::Copy => return None,
+            InstallMethod::Hardlink => fs::hard_link(source, target),
+            InstallMethod::Symlink => {
+                fs::canonicalize(source).and_then(|source| symlink(&source, target))
+            }
+            InstallMethod::Reflink => reflink_copy::reflink(source, target),
+        };
+
+        if result.is_err() {
+            let _ = fs::remove_file(target);
+            return None;
+        }
+
+        Some(self)
+    }
+}
+
+impl fmt::Display for InstallMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            InstallMethod::Copy => "copy",
+            InstallMethod::Hardlink => "hardlink",
+            InstallMethod::Symlink => "symlink",
+            InstallMethod::Reflink => "reflink",
+        };
+        write!(f, "{message}")
+    }
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, target: &Path) -> io::Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, target)
+    } else {
+        std::os::windows::fs::symlink_file(source, target)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_source: &Path, _target: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
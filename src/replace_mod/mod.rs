@@ -1,5 +1,8 @@
+pub mod install_strategy;
+pub mod plan;
 pub mod replace_mods;
 pub mod replace_music;
+pub mod transaction;
 
 use indicatif::{HumanBytes, ProgressBar};
 
@@ -0,0 +1,447 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use indicatif::HumanBytes;
+
+use crate::{
+    compose_music::TrackMusic,
+    configs::{
+        install_receipt::ModsFolderSurvey,
+        paths_config::{GamePath, ModsPath, ProgramPath},
+    },
+    file_hash::HashCache,
+    hotline_mod::{HotlineMod, HotlineModName, Music},
+    wad::{
+        conflicts::ConflictReport,
+        validate::{is_wad_file, validate_wad},
+    },
+};
+
+use super::{
+    install_strategy::InstallMethod, replace_mods::is_patchwad, replace_music::game_music_path,
+};
+
+/// Everything a switch is going to do, worked out before touching any file.
+#[derive(Debug)]
+pub struct SwitchPlan {
+    mod_name: HotlineModName,
+    game_path: GamePath,
+    mods_path: ModsPath,
+    method: InstallMethod,
+    music: Option<PlannedCopy>,
+    music_changes: bool,
+    copies: Vec<PlannedCopy>,
+    skips: Vec<PlannedCopy>,
+    deletes: Vec<PathBuf>,
+    modified: Vec<PathBuf>,
+    unmanaged: Vec<PathBuf>,
+    blocked_by: Option<PathBuf>,
+    corrupt: Vec<CorruptWad>,
+    conflicts: ConflictReport,
+}
+
+/// The music a switch installs.
+#[derive(Debug, Clone, Copy)]
+pub enum PlannedMusic<'a> {
+    /// A music wad that's already there.
+    Wad(&'a Music),
+    /// Music composed from a mod's single tracks, which is only written once the switch runs.
+    Composed(&'a TrackMusic),
+}
+
+/// A file or folder the switch copies from the library into the game folders.
+#[derive(Debug, Clone)]
+pub struct PlannedCopy {
+    source: PathBuf,
+    destination: PathBuf,
+    size: u64,
+}
+
+impl SwitchPlan {
+    pub fn new(
+        game_path: &GamePath,
+        mods_path: &ModsPath,
+        hm_mod: &HotlineMod,
+        music: Option<PlannedMusic>,
+        survey: &ModsFolderSurvey,
+        method: InstallMethod,
+        cache: &mut HashCache,
+    ) -> io::Result<Self> {
+        // Music that isn't composed yet can't be compared or validated, it's new either way.
+        let composing = matches!(
+            music,
+            Some(PlannedMusic::Composed(track_music)) if !track_music.is_up_to_date()
+        );
+        let music = match music {
+            Some(PlannedMusic::Wad(music)) => {
+                Some(PlannedCopy::new(music.path(), game_music_path(game_path))?)
+            }
+            Some(PlannedMusic::Composed(track_music)) => Some(PlannedCopy {
+                source: track_music.music().to_path_buf(),
+                destination: game_music_path(game_path),
+                size: track_music.size(),
+            }),
+            None => None,
+        };
+        let music_changes = match &music {
+            Some(_) if composing => true,
+            Some(music) if music.destination.is_file() => {
+                !cache.same_contents(&music.source, &music.destination)?
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        let mut copies = Vec::new();
+        let mut skips = Vec::new();
+        for source in hm_mod.mods().mods() {
+            let Some(file_name) = source.file_name() else {
+                continue;
+            };
+            let copy = PlannedCopy::new(source, mods_path.path().join(file_name))?;
+
+            if survey.managed().contains(&copy.destination)
+                && cache.same_contents(&copy.source, &copy.destination)?
+            {
+                skips.push(copy);
+            } else {
+                copies.push(copy);
+            }
+        }
+
+        let blocked_by = copies
+            .iter()
+            .map(|copy| &copy.destination)
+            .find(|destination| !survey.can_replace(destination))
+            .cloned();
+        let corrupt = music
+            .iter()
+            .filter(|_| music_changes && !composing)
+            .chain(&copies)
+            .map(|copy| copy.source.as_path())
+            .filter(|source| is_wad_file(source))
+            .filter_map(|source| {
+                validate_wad(source).err().map(|err| CorruptWad {
+                    path: source.to_path_buf(),
+                    reason: err.to_string(),
+                })
+            })
+            .collect();
+        // Every patchwad in the mods folder once the switch is done, the mod's and the user's.
+        let conflicts = ConflictReport::find(
+            copies
+                .iter()
+                .chain(&skips)
+                .map(|copy| copy.source.as_path())
+                .filter(|source| is_patchwad(source))
+                .chain(survey.modified().iter().map(PathBuf::as_path))
+                .chain(survey.unmanaged().iter().map(PathBuf::as_path)),
+        );
+        let deletes = survey
+            .managed()
+            .iter()
+            .filter(|managed| !skips.iter().any(|skip| skip.destination == **managed))
+            .cloned()
+            .collect();
+
+        Ok(SwitchPlan {
+            mod_name: hm_mod.name().clone(),
+            game_path: game_path.clone(),
+            mods_path: mods_path.clone(),
+            method,
+            music,
+            music_changes,
+            copies,
+            skips,
+            deletes,
+            modified: survey.modified().to_vec(),
+            unmanaged: survey.unmanaged().to_vec(),
+            blocked_by,
+            corrupt,
+            conflicts,
+        })
+    }
+
+    pub fn mod_name(&self) -> &HotlineModName {
+        &self.mod_name
+    }
+
+    pub fn game_path(&self) -> &GamePath {
+        &self.game_path
+    }
+
+    pub fn mods_path(&self) -> &ModsPath {
+        &self.mods_path
+    }
+
+    /// The method the files are installed with, before falling back to a copy.
+    pub fn method(&self) -> InstallMethod {
+        self.method
+    }
+
+    /// The music to install, if the mod or the default game has any.
+    pub fn music(&self) -> Option<&PlannedCopy> {
+        self.music.as_ref()
+    }
+
+    /// False when the installed music is already the one being installed.
+    pub fn music_changes(&self) -> bool {
+        self.music_changes
+    }
+
+    /// The mod's files that aren't installed yet, or differ from the installed ones.
+    pub fn copies(&self) -> &[PlannedCopy] {
+        &self.copies
+    }
+
+    /// The mod's files that are already installed exactly as they are, left untouched.
+    pub fn skips(&self) -> &[PlannedCopy] {
+        &self.skips
+    }
+
+    /// Every file of the mod in the mods folder once the switch is done.
+    pub fn installed_mods(&self) -> impl Iterator<Item = &PlannedCopy> {
+        self.copies.iter().chain(&self.skips)
+    }
+
+    /// Files the manager installed before and removes now.
+    pub fn deletes(&self) -> &[PathBuf] {
+        &self.deletes
+    }
+
+    /// Files the manager installed before, left in place because they were changed since.
+    pub fn modified(&self) -> &[PathBuf] {
+        &self.modified
+    }
+
+    /// Patchwads the manager didn't install, left in place.
+    pub fn unmanaged(&self) -> &[PathBuf] {
+        &self.unmanaged
+    }
+
+    /// A file the user owns that one of the copies would overwrite, which stops the switch.
+    pub fn blocked_by(&self) -> Option<&Path> {
+        self.blocked_by.as_deref()
+    }
+
+    /// Assets more than one of the installed patchwads provide.
+    pub fn conflicts(&self) -> &ConflictReport {
+        &self.conflicts
+    }
+
+    /// Wads of the mod that are truncated or corrupt, which also stop the switch.
+    pub fn corrupt(&self) -> &[CorruptWad] {
+        &self.corrupt
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.music_bytes() + self.mods_bytes()
+    }
+
+    /// The free space in the game and mods folders, next to the bytes each one receives.
+    pub fn free_space(&self) -> Vec<FreeSpace> {
+        let game_dir = self.game_path.path();
+        let mods_dir = self.mods_path.path();
+
+        if game_dir == mods_dir {
+            return vec![FreeSpace::new(game_dir, self.bytes_written())];
+        }
+
+        vec![
+            FreeSpace::new(game_dir, self.music_bytes()),
+            FreeSpace::new(mods_dir, self.mods_bytes()),
+        ]
+    }
+
+    fn music_bytes(&self) -> u64 {
+        self.music
+            .as_ref()
+            .filter(|_| self.music_changes)
+            .map_or(0, |music| music.size)
+    }
+
+    fn mods_bytes(&self) -> u64 {
+        self.copies.iter().map(|copy| copy.size).sum()
+    }
+}
+
+impl PlannedCopy {
+    fn new(source: &Path, destination: PathBuf) -> io::Result<Self> {
+        Ok(PlannedCopy {
+            source: source.to_path_buf(),
+            destination,
+            size: path_size(source)?,
+        })
+    }
+
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// A wad that failed validation, and why.
+#[derive(Debug, Clone)]
+pub struct CorruptWad {
+    path: PathBuf,
+    reason: String,
+}
+
+impl CorruptWad {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+/// How much room a folder has for what a switch writes into it.
+#[derive(Debug)]
+pub struct FreeSpace {
+    dir: PathBuf,
+    available: Option<u64>,
+    needed: u64,
+}
+
+impl FreeSpace {
+    fn new(dir: &Path, needed: u64) -> Self {
+        FreeSpace {
+            dir: dir.to_path_buf(),
+            available: fs4::available_space(dir).ok(),
+            needed,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// `None` when the free space couldn't be read.
+    pub fn available(&self) -> Option<u64> {
+        self.available
+    }
+
+    pub fn is_enough(&self) -> bool {
+        self.available
+            .is_none_or(|available| available >= self.needed)
+    }
+}
+
+impl fmt::Display for SwitchPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Switching to {}:", self.mod_name)?;
+
+        match &self.music {
+            Some(music) if self.music_changes => writeln!(
+                f,
+                "  Music: replaced with {} ({})",
+                file_name(&music.source),
+                HumanBytes(music.size)
+            )?,
+            Some(_) => writeln!(f, "  Music: already installed")?,
+            None => writeln!(f, "  Music: left as it is, there's no music to install")?,
+        }
+
+        for path in &self.deletes {
+            writeln!(f, "  Delete: {}", file_name(path))?;
+        }
+        for copy in &self.copies {
+            writeln!(
+                f,
+                "  Copy: {} ({})",
+                file_name(&copy.source),
+                HumanBytes(copy.size)
+            )?;
+        }
+        for skip in &self.skips {
+            writeln!(f, "  Keep: {} (already installed)", file_name(&skip.source))?;
+        }
+        for path in &self.modified {
+            writeln!(
+                f,
+                "  Keep: {} (changed after the manager installed it)",
+                file_name(path)
+            )?;
+        }
+        for path in &self.unmanaged {
+            writeln!(
+                f,
+                "  Keep: {} (not installed by the manager)",
+                file_name(path)
+            )?;
+        }
+
+        write!(f, "{}", self.conflicts)?;
+
+        if self.method != InstallMethod::Copy {
+            writeln!(
+                f,
+                "  Install method: {}, copying what can't be installed that way",
+                self.method
+            )?;
+        }
+        writeln!(f, "  Bytes written: {}", HumanBytes(self.bytes_written()))?;
+        for free_space in self.free_space() {
+            let available = free_space.available.map_or_else(
+                || "unknown".to_string(),
+                |bytes| HumanBytes(bytes).to_string(),
+            );
+            let warning = if free_space.is_enough() {
+                ""
+            } else {
+                ", not enough for this switch"
+            };
+            writeln!(
+                f,
+                "  Free space in {}: {available}{warning}",
+                free_space.dir.display()
+            )?;
+        }
+
+        if let Some(path) = &self.blocked_by {
+            writeln!(
+                f,
+                "  Blocked: {} is in the way and wasn't installed by the manager",
+                file_name(path)
+            )?;
+        }
+        for corrupt in &self.corrupt {
+            writeln!(
+                f,
+                "  Blocked: {} is damaged. {}",
+                corrupt.path.display(),
+                corrupt.reason
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+}
+
+/// The size of a file, or of everything inside a folder.
+fn path_size(path: &Path) -> io::Result<u64> {
+    let metadata = fs::metadata(path)?;
+
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    fs::read_dir(path)?.try_fold(0, |total, entry| Ok(total + path_size(&entry?.path())?))
+}
@@ -1,7 +1,7 @@
 use std::{
     fs::{self, DirEntry},
     io,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use fs_extra::{copy_items_with_progress, dir::TransitProcessResult};
@@ -10,20 +10,47 @@ use thiserror::Error;
 
 use crate::{
     configs::paths_config::{ModsPath, ProgramPath},
-    hotline_mod::{AssociatedMods, HotlineModName},
+    hotline_mod::HotlineModName,
 };
 
-use super::update_progress_bar;
+use super::{
+    install_strategy::InstallMethod,
+    transaction::{staging_dir, StagedFile},
+    update_progress_bar,
+};
 
-pub fn replace_mods(
+/// Places the mod's files into the mods folder's staging area with the given method, copying the
+/// ones it can't handle, without touching the installed patchwads.
+pub fn stage_mods(
     mods_path: &ModsPath,
-    associated_mods: &AssociatedMods,
+    sources: &[&Path],
     mod_name: &HotlineModName,
-) -> Result<(), ReplaceModError> {
-    remove_mods_in_mods_dir(mods_path)?;
+    method: InstallMethod,
+) -> Result<Vec<StagedFile>, ReplaceModError> {
+    let staging_dir = staging_dir(mods_path.path());
+    let mut staged = Vec::new();
+    let mut to_copy = Vec::new();
+
+    for source in sources {
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        let staged_path = staging_dir.join(file_name);
+        let destination = mods_path.path().join(file_name);
+
+        let linked = method.link(source, &staged_path);
+        if linked.is_none() {
+            to_copy.push(*source);
+        }
+        staged.push(StagedFile::new(
+            staged_path,
+            destination,
+            linked.unwrap_or(InstallMethod::Copy),
+        ));
+    }
 
-    if associated_mods.mods().is_empty() {
-        return Ok(());
+    if to_copy.is_empty() {
+        return Ok(staged);
     }
 
     let progress_bar = progress_bar(mod_name);
@@ -35,15 +62,24 @@ pub fn replace_mods(
         );
         TransitProcessResult::ContinueOrAbort
     };
-    match copy_items_with_progress(
-        associated_mods.mods(),
-        mods_path.path(),
+    copy_items_with_progress(
+        &to_copy,
+        &staging_dir,
         &fs_extra::dir::CopyOptions::new(),
         handler,
-    ) {
-        Ok(_) => Ok(()),
-        Err(error) => Err(ReplaceModError::FsExtra(error)),
-    }
+    )?;
+
+    Ok(staged)
+}
+
+/// The patchwads currently in the mods folder, which a switch replaces.
+pub fn installed_patchwads(mods_path: &ModsPath) -> Result<Vec<PathBuf>, ReplaceModError> {
+    Ok(fs::read_dir(mods_path.path())
+        .map_err(ReplaceModError::ReadingModsDirectory)?
+        .filter_map(Result::ok)
+        .filter(file_is_patchwad)
+        .map(|file| file.path())
+        .collect())
 }
 
 #[derive(Error, Debug)]
@@ -52,29 +88,9 @@ pub enum ReplaceModError {
     FsExtra(#[from] fs_extra::error::Error),
     #[error("Error reading the mods directory")]
     ReadingModsDirectory(io::Error),
-    #[error("Error removing the mods directory")]
-    DeletingModFile(io::Error, Box<Path>),
-}
-
-fn remove_mods_in_mods_dir(mods_path: &ModsPath) -> Result<(), ReplaceModError> {
-    let files_to_remove = fs::read_dir(mods_path.path())
-        .map_err(ReplaceModError::ReadingModsDirectory)?
-        .filter_map(Result::ok)
-        .filter(file_is_patchwad)
-        .collect::<Vec<_>>();
-
-    for file in files_to_remove {
-        let path = file.path();
-        fs::remove_file(&path)
-            .map_err(|err| ReplaceModError::DeletingModFile(err, path.into_boxed_path()))?;
-    }
-
-    Ok(())
 }
 
-fn file_is_patchwad(dir_entry: &DirEntry) -> bool {
-    let path = dir_entry.path();
-
+pub fn is_patchwad(path: &Path) -> bool {
     let is_file = path.is_file();
     let is_patchwad = path
         .extension()
@@ -83,6 +99,10 @@ fn file_is_patchwad(dir_entry: &DirEntry) -> bool {
     is_file && is_patchwad
 }
 
+fn file_is_patchwad(dir_entry: &DirEntry) -> bool {
+    is_patchwad(&dir_entry.path())
+}
+
 fn progress_bar(mod_name: &HotlineModName) -> ProgressBar {
     let progress_bar_message = format!("Copying {mod_name} mods.");
     let style = ProgressStyle::default_bar().template("{msg}").unwrap();
@@ -1,20 +1,38 @@
+use std::path::{Path, PathBuf};
+
 use fs_extra::file::{copy_with_progress, CopyOptions, TransitProcess};
 use indicatif::{ProgressBar, ProgressStyle};
 
 use crate::{
     configs::paths_config::{GamePath, ProgramPath},
-    hotline_mod::{HotlineModName, Music},
+    hotline_mod::HotlineModName,
 };
 
-use super::update_progress_bar;
+use super::{
+    install_strategy::InstallMethod,
+    transaction::{staging_dir, StagedFile},
+    update_progress_bar,
+};
 
-pub fn replace_music(
+/// Places the music into the game folder's staging area with the given method, copying it when
+/// the method isn't possible, without touching the installed music.
+pub fn stage_music(
     game_path: &GamePath,
-    music: &Music,
+    music: &Path,
     mod_name: &HotlineModName,
-) -> Result<(), ReplaceMusicError> {
+    method: InstallMethod,
+) -> Result<StagedFile, ReplaceMusicError> {
+    let staged_music_path = staging_dir(game_path.path()).join(MUSIC_FILE_NAME);
+
+    if let Some(method) = method.link(music, &staged_music_path) {
+        return Ok(StagedFile::new(
+            staged_music_path,
+            game_music_path(game_path),
+            method,
+        ));
+    }
+
     let copy_options = default_copy_options();
-    let game_music_path = game_path.path().join(MUSIC_FILE_NAME);
     let progress_bar = progress_bar(mod_name);
     let handler = |transit_process: TransitProcess| {
         update_progress_bar(
@@ -24,12 +42,20 @@ pub fn replace_music(
         );
     };
 
-    match copy_with_progress(music, game_music_path, &copy_options, handler) {
-        Ok(_) => Ok(()),
+    match copy_with_progress(music, &staged_music_path, &copy_options, handler) {
+        Ok(_) => Ok(StagedFile::new(
+            staged_music_path,
+            game_music_path(game_path),
+            InstallMethod::Copy,
+        )),
         Err(error) => Err(ReplaceMusicError::from(error)),
     }
 }
 
+pub fn game_music_path(game_path: &GamePath) -> PathBuf {
+    game_path.path().join(MUSIC_FILE_NAME)
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ReplaceMusicError {
     #[error("Error trying to copy the music bytes.")]
@@ -50,4 +76,4 @@ fn default_copy_options() -> CopyOptions {
     copy_options
 }
 
-const MUSIC_FILE_NAME: &str = "hlm2_music_desktop.wad";
+pub const MUSIC_FILE_NAME: &str = "hlm2_music_desktop.wad";
@@ -0,0 +1,289 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::configs::paths_config::ProgramPath;
+
+use super::{
+    install_strategy::InstallMethod,
+    plan::{PlannedCopy, SwitchPlan},
+    replace_mods::{stage_mods, ReplaceModError},
+    replace_music::{stage_music, ReplaceMusicError},
+};
+
+/// A mod switch that can be undone until it's committed.
+///
+/// Every new file is first copied into a staging folder next to where it goes, so a failed copy
+/// never touches the game. Applying moves the files being replaced into a backup folder and the
+/// staged files into place, which are renames inside the same folder. If anything goes wrong,
+/// [`SwitchTransaction::rollback`] puts the previous music and patchwads back.
+///
+/// It carries out a [`SwitchPlan`], so only the files the manager installed are replaced and the
+/// user's own files are never touched.
+#[derive(Debug)]
+pub struct SwitchTransaction {
+    work_dirs: Vec<PathBuf>,
+    staged: Vec<StagedFile>,
+    replaced: Vec<PathBuf>,
+    backups: Vec<Backup>,
+    placed: Vec<PathBuf>,
+}
+
+/// A file copied into a staging folder, waiting to be moved to its destination.
+#[derive(Debug)]
+pub struct StagedFile {
+    staged: PathBuf,
+    destination: PathBuf,
+    method: InstallMethod,
+}
+
+impl StagedFile {
+    pub fn new(staged: PathBuf, destination: PathBuf, method: InstallMethod) -> Self {
+        StagedFile {
+            staged,
+            destination,
+            method,
+        }
+    }
+
+    pub fn staged(&self) -> &Path {
+        &self.staged
+    }
+
+    pub fn destination(&self) -> &Path {
+        &self.destination
+    }
+
+    /// How the file got into the staging folder, after falling back to a copy if needed.
+    pub fn method(&self) -> InstallMethod {
+        self.method
+    }
+}
+
+#[derive(Debug)]
+struct Backup {
+    original: PathBuf,
+    backup: PathBuf,
+}
+
+impl SwitchTransaction {
+    /// Copies the mod's files next to the game, leaving the installed ones untouched.
+    pub fn stage(plan: &SwitchPlan) -> Result<Self, SwitchError> {
+        let game_dir = plan.game_path().path();
+        let mods_dir = plan.mods_path().path();
+        let mut work_dirs = vec![game_dir.to_path_buf()];
+        if mods_dir != game_dir {
+            work_dirs.push(mods_dir.to_path_buf());
+        }
+
+        for dir in &work_dirs {
+            let backup_dir = backup_dir(dir);
+            if backup_dir.exists() {
+                return Err(SwitchError::InterruptedSwitch(backup_dir.into_boxed_path()));
+            }
+        }
+
+        if let Some(destination) = plan.blocked_by() {
+            return Err(SwitchError::FileInTheWay(destination.into()));
+        }
+        if let Some(corrupt) = plan.corrupt().first() {
+            return Err(SwitchError::CorruptWad(
+                corrupt.path().into(),
+                corrupt.reason().into(),
+            ));
+        }
+
+        let mut transaction = SwitchTransaction {
+            work_dirs,
+            staged: Vec::new(),
+            replaced: plan.deletes().to_vec(),
+            backups: Vec::new(),
+            placed: Vec::new(),
+        };
+
+        if let Err(err) = transaction.stage_files(plan) {
+            transaction.remove_work_dirs();
+            return Err(err);
+        }
+
+        Ok(transaction)
+    }
+
+    /// Moves the replaced files into the backup folders and the staged files into place.
+    pub fn apply(&mut self) -> Result<(), SwitchError> {
+        let occupied_destinations = self
+            .staged
+            .iter()
+            .map(|file| file.destination.clone())
+            .filter(|destination| {
+                fs::symlink_metadata(destination).is_ok() && !self.replaced.contains(destination)
+            })
+            .collect::<Vec<_>>();
+        let to_back_up = self
+            .replaced
+            .iter()
+            .cloned()
+            .chain(occupied_destinations)
+            .collect::<Vec<_>>();
+
+        for original in to_back_up {
+            let backup = backup_dir(parent(&original)).join(file_name(&original));
+            fs::create_dir_all(parent(&backup))
+                .and_then(|()| fs::rename(&original, &backup))
+                .map_err(|err| SwitchError::BackingUp(err, original.clone().into_boxed_path()))?;
+            self.backups.push(Backup { original, backup });
+        }
+
+        for file in &self.staged {
+            fs::rename(&file.staged, &file.destination).map_err(|err| {
+                SwitchError::Installing(err, file.destination.clone().into_boxed_path())
+            })?;
+            self.placed.push(file.destination.clone());
+        }
+
+        Ok(())
+    }
+
+    /// The method each file was installed with, by destination.
+    pub fn methods(&self) -> impl Iterator<Item = (&Path, InstallMethod)> {
+        self.staged
+            .iter()
+            .map(|file| (file.destination.as_path(), file.method))
+    }
+
+    /// Removes the backups and what's left of the staging folders.
+    pub fn commit(self) {
+        self.remove_work_dirs();
+    }
+
+    /// Removes what the switch installed and puts the previous files back.
+    ///
+    /// Keeps going after a failure so as much as possible is restored, returning the first error.
+    pub fn rollback(self) -> Result<(), SwitchError> {
+        let mut first_error = None;
+
+        for placed in self.placed.iter().rev() {
+            if let Err(err) = remove_path(placed) {
+                first_error.get_or_insert(SwitchError::RollingBack(
+                    err,
+                    placed.clone().into_boxed_path(),
+                ));
+            }
+        }
+
+        for backup in self.backups.iter().rev() {
+            if let Err(err) = fs::rename(&backup.backup, &backup.original) {
+                first_error.get_or_insert(SwitchError::RollingBack(
+                    err,
+                    backup.original.clone().into_boxed_path(),
+                ));
+            }
+        }
+
+        // The backups are the only copy of the previous files, so they stay where they are.
+        if let Some(err) = first_error {
+            return Err(err);
+        }
+
+        self.remove_work_dirs();
+        Ok(())
+    }
+
+    fn stage_files(&mut self, plan: &SwitchPlan) -> Result<(), SwitchError> {
+        for dir in &self.work_dirs {
+            let staging_dir = staging_dir(dir);
+            remove_if_exists(&staging_dir)
+                .and_then(|()| fs::create_dir_all(&staging_dir))
+                .map_err(|err| SwitchError::Staging(err, staging_dir.into_boxed_path()))?;
+        }
+
+        if let Some(music) = plan.music().filter(|_| plan.music_changes()) {
+            self.staged.push(stage_music(
+                plan.game_path(),
+                music.source(),
+                plan.mod_name(),
+                plan.method(),
+            )?);
+        }
+
+        let sources = plan
+            .copies()
+            .iter()
+            .map(PlannedCopy::source)
+            .collect::<Vec<_>>();
+        self.staged.extend(stage_mods(
+            plan.mods_path(),
+            &sources,
+            plan.mod_name(),
+            plan.method(),
+        )?);
+
+        Ok(())
+    }
+
+    fn remove_work_dirs(&self) {
+        for dir in &self.work_dirs {
+            let _ = remove_if_exists(&staging_dir(dir));
+            let _ = remove_if_exists(&backup_dir(dir));
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SwitchError {
+    #[error("{0}")]
+    ReplaceMusic(#[from] ReplaceMusicError),
+    #[error("{0}")]
+    ReplaceMods(#[from] ReplaceModError),
+    #[error("Error preparing the folder {1}. Error: {0}")]
+    Staging(io::Error, Box<Path>),
+    #[error("Error moving {1} out of the way. Error: {0}")]
+    BackingUp(io::Error, Box<Path>),
+    #[error("Error installing {1}. Error: {0}")]
+    Installing(io::Error, Box<Path>),
+    #[error("Error restoring {1}, your previous files are still in the {BACKUP_DIR_NAME} folders. Error: {0}")]
+    RollingBack(io::Error, Box<Path>),
+    #[error("{0} is in the way and wasn't installed by the manager, or was changed since. Move it out of your mods folder and try again.")]
+    FileInTheWay(Box<Path>),
+    #[error("{} is damaged, so nothing was installed. Download the mod again and try again. {1}", .0.display())]
+    CorruptWad(Box<Path>, Box<str>),
+    #[error("A previous mod switch didn't finish. Your previous files are in {0}, move them back or delete that folder before switching again.")]
+    InterruptedSwitch(Box<Path>),
+}
+
+pub fn staging_dir(dir: &Path) -> PathBuf {
+    dir.join(STAGING_DIR_NAME)
+}
+
+fn backup_dir(dir: &Path) -> PathBuf {
+    dir.join(BACKUP_DIR_NAME)
+}
+
+fn parent(path: &Path) -> &Path {
+    path.parent().unwrap_or(Path::new("."))
+}
+
+fn file_name(path: &Path) -> &std::ffi::OsStr {
+    path.file_name().unwrap_or(path.as_os_str())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match remove_path(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+const STAGING_DIR_NAME: &str = ".hm_mod_manager_staging";
+const BACKUP_DIR_NAME: &str = ".hm_mod_manager_backup";
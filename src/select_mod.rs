@@ -1,15 +1,84 @@
+use std::{
+    cmp::Reverse,
+    fmt::{self, Write},
+    time::SystemTime,
+};
+
+use indicatif::HumanBytes;
 use inquire::InquireError;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{functions::prompt_user_select, hotline_mod::HotlineMod};
+use crate::{
+    configs::Configs,
+    functions::time_ago,
+    hotline_mod::HotlineMod,
+    mod_manifest::ModManifest,
+    picker::{Picker, PickerOption, PickerOrder},
+    prompter::Prompter,
+    replace_mod::replace_mods::is_patchwad,
+};
+
+/// The orders the mod selector can show the mods in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModSort {
+    #[default]
+    Name,
+    RecentlyUsed,
+    Size,
+}
+
+impl ModSort {
+    pub const ALL: [ModSort; 3] = [ModSort::Name, ModSort::RecentlyUsed, ModSort::Size];
+}
+
+impl fmt::Display for ModSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModSort::Name => write!(f, "name"),
+            ModSort::RecentlyUsed => write!(f, "recently used"),
+            ModSort::Size => write!(f, "size"),
+        }
+    }
+}
 
-pub fn select_mod(mods: &[HotlineMod]) -> Result<HotlineMod, ChangeCurrentModError> {
+pub fn select_mod(
+    prompter: &mut dyn Prompter,
+    mods: &[HotlineMod],
+    configs: &Configs,
+) -> Result<HotlineMod, ChangeCurrentModError> {
     if mods.is_empty() {
         return Err(ChangeCurrentModError::EmptyMods);
     }
 
-    match prompt_user_select("What mod do you wish to use?", mods.to_vec()) {
-        Ok(desired_mod) => Ok(desired_mod),
+    let summaries = mods
+        .iter()
+        .map(|hm_mod| ModSummary {
+            hm_mod,
+            size: hm_mod.size(),
+            last_used: configs.last_used(hm_mod.name()),
+        })
+        .collect::<Vec<_>>();
+
+    let options = summaries.iter().map(ModSummary::option).collect();
+    let picker = ModSort::ALL
+        .iter()
+        .fold(
+            Picker::new("What mod do you wish to use?", options),
+            |picker, sort| {
+                picker.with_order(PickerOrder::new(sort.to_string(), sort.order(&summaries)))
+            },
+        )
+        .with_starting_order(
+            ModSort::ALL
+                .iter()
+                .position(|sort| *sort == configs.settings().mod_sort())
+                .unwrap_or_default(),
+        );
+
+    match prompter.pick(&picker) {
+        Ok(index) => Ok(mods[index].clone()),
         Err(InquireError::OperationCanceled) => Err(ChangeCurrentModError::UserCanceledOperation),
         Err(InquireError::OperationInterrupted) => {
             Err(ChangeCurrentModError::UserExitedApplication)
@@ -29,3 +98,81 @@ pub enum ChangeCurrentModError {
     #[error("Inquire error")]
     InquireError(#[from] InquireError),
 }
+
+/// A mod in the selector, with what's shown and searched for it.
+struct ModSummary<'a> {
+    hm_mod: &'a HotlineMod,
+    size: u64,
+    last_used: Option<SystemTime>,
+}
+
+impl ModSummary<'_> {
+    fn option(&self) -> PickerOption {
+        let manifest = self.hm_mod.manifest();
+
+        let mut label = self.hm_mod.to_string();
+        if let Some(byline) = manifest.and_then(ModManifest::byline) {
+            let _ = write!(label, " ({byline})");
+        }
+        if let Some(manifest) = manifest.filter(|manifest| !manifest.tags().is_empty()) {
+            let _ = write!(label, " [{}]", manifest.tags().join(", "));
+        }
+
+        let mut keywords = self
+            .hm_mod
+            .name()
+            .directory_name()
+            .to_string_lossy()
+            .into_owned();
+        if let Some(author) = manifest.and_then(|manifest| manifest.author()) {
+            keywords.push(' ');
+            keywords.push_str(author);
+        }
+
+        PickerOption::new(label, keywords, self.detail())
+    }
+
+    /// Music present or not, patchwad count, total size and last used.
+    fn detail(&self) -> String {
+        let music = match (self.hm_mod.music(), self.hm_mod.music_tracks()) {
+            (Some(_), _) => "yes",
+            (None, Some(_)) => "single tracks",
+            (None, None) => "no",
+        };
+        let patchwads = self
+            .hm_mod
+            .mods()
+            .mods()
+            .iter()
+            .filter(|path| is_patchwad(path))
+            .count();
+        let last_used = self.last_used.map_or_else(|| "never".to_string(), time_ago);
+
+        format!(
+            "Music: {music} | Patchwads: {patchwads} | Size: {} | Last used: {last_used}",
+            HumanBytes(self.size)
+        )
+    }
+}
+
+impl ModSort {
+    /// The indices of the summaries in this order, by name when they're equal.
+    fn order(self, summaries: &[ModSummary]) -> Vec<usize> {
+        let mut indices = (0..summaries.len()).collect::<Vec<_>>();
+        let name = |index: &usize| summaries[*index].hm_mod.to_string().to_lowercase();
+
+        match self {
+            ModSort::Name => indices.sort_by_cached_key(name),
+            ModSort::RecentlyUsed => {
+                indices.sort_by_cached_key(|index| {
+                    (Reverse(summaries[*index].last_used), name(index))
+                });
+            }
+            ModSort::Size => {
+                indices.sort_by_cached_key(|index| (Reverse(summaries[*index].size), name(index)));
+            }
+        }
+
+        indices
+    }
+}
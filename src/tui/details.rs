@@ -0,0 +1,113 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use indicatif::HumanBytes;
+use ratatui::{
+    style::Stylize,
+    text::{Line, Text},
+};
+
+use crate::{
+    functions::time_ago,
+    hotline_mod::HotlineMod,
+    replace_mod::replace_mods::is_patchwad,
+    wad::conflicts::{sort_by_load_order, ConflictReport},
+};
+
+/// What the details pane shows about a mod: its manifest, files, sizes and the assets more than
+/// one of its patchwads provides.
+pub(super) fn mod_details(
+    hm_mod: &HotlineMod,
+    mod_dir: &Path,
+    last_used: Option<SystemTime>,
+) -> Text<'static> {
+    let mut lines = vec![
+        Line::from(hm_mod.to_string().bold()),
+        Line::from(format!("Folder: {}", mod_dir.display())),
+        Line::from(format!("Size: {}", HumanBytes(hm_mod.size()))),
+        Line::from(format!(
+            "Last used: {}",
+            last_used.map_or_else(|| "never".to_string(), time_ago)
+        )),
+    ];
+
+    if let Some(manifest) = hm_mod.manifest() {
+        lines.push(Line::default());
+        lines.extend(
+            manifest
+                .to_string()
+                .lines()
+                .map(|line| Line::from(line.trim_start().to_string())),
+        );
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::from("Music".bold()));
+    match hm_mod.music() {
+        Some(music) => lines.push(file_line(music.path())),
+        None if hm_mod.music_tracks().is_none() => {
+            lines.push(Line::from("  None, the original music plays"));
+        }
+        None => {}
+    }
+    if let Some(music_tracks) = hm_mod.music_tracks() {
+        match music_tracks.tracks() {
+            Ok(tracks) => {
+                lines.push(Line::from(format!(
+                    "  {} single tracks in {}:",
+                    tracks.len(),
+                    music_tracks.path().display()
+                )));
+                lines.extend(tracks.iter().map(|(name, path)| {
+                    Line::from(format!("    {name} ({})", HumanBytes(file_size(path))))
+                }));
+            }
+            Err(err) => lines.push(Line::from(
+                format!("  Error reading the tracks: {err}").red(),
+            )),
+        }
+    }
+
+    let mut files = hm_mod
+        .mods()
+        .mods()
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>();
+    sort_by_load_order(&mut files);
+
+    lines.push(Line::default());
+    lines.push(Line::from("Mods files".bold()));
+    if files.is_empty() {
+        lines.push(Line::from("  None"));
+    }
+    lines.extend(files.iter().map(|path| file_line(path)));
+
+    let conflicts = ConflictReport::in_order(files.into_iter().filter(|path| is_patchwad(path)));
+    lines.push(Line::default());
+    lines.push(Line::from("Conflicts".bold()));
+    if conflicts.is_empty() {
+        lines.push(Line::from("  None between its patchwads"));
+    }
+    lines.extend(
+        conflicts
+            .conflicts()
+            .iter()
+            .map(|conflict| Line::from(format!("  {conflict}").yellow())),
+    );
+
+    Text::from(lines)
+}
+
+fn file_line(path: &Path) -> Line<'static> {
+    Line::from(format!(
+        "  {} ({})",
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy(),
+        HumanBytes(file_size(path))
+    ))
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
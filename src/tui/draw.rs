@@ -0,0 +1,168 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{configs::paths_config::ProgramPath, fingerprint::InstalledMod};
+
+use super::{Popup, Status, Tui, KEYS};
+
+pub(super) fn draw(frame: &mut Frame, tui: &mut Tui) {
+    let [main, status_bar, status, keys] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(6),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [library, details] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+    draw_library(frame, tui, library);
+    draw_details(frame, tui, details);
+    draw_status_bar(frame, tui, status_bar);
+    draw_status(frame, tui, status);
+    frame.render_widget(keys_line(KEYS), keys);
+
+    if let Some(popup) = &tui.popup {
+        draw_popup(frame, popup);
+    }
+}
+
+fn draw_library(frame: &mut Frame, tui: &mut Tui, area: Rect) {
+    let mods = tui.mod_manager.all_mods().mods();
+    let installed = match &tui.installed {
+        Some(InstalledMod::Active(name) | InstalledMod::Modified(name)) => Some(name),
+        _ => None,
+    };
+
+    let items = tui.library.iter().map(|index| {
+        let hm_mod = &mods[*index];
+        if Some(hm_mod.name()) == installed {
+            Line::from(format!("* {hm_mod}")).green()
+        } else {
+            Line::from(format!("  {hm_mod}"))
+        }
+    });
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" Library ({}) ", mods.len())))
+        .highlight_style(Style::new().reversed());
+
+    frame.render_stateful_widget(list, area, &mut tui.selected);
+}
+
+fn draw_details(frame: &mut Frame, tui: &Tui, area: Rect) {
+    let block = Block::bordered().title(" Details ");
+    let paragraph = match &tui.details {
+        Some((_, details)) => Paragraph::new(details.clone()),
+        None => Paragraph::new("You have no mods in your folder right now. Try downloading new mods or bringing your existing mods to this folder.".dark_gray()),
+    };
+
+    frame.render_widget(paragraph.wrap(Wrap { trim: false }).block(block), area);
+}
+
+fn draw_status_bar(frame: &mut Frame, tui: &Tui, area: Rect) {
+    let installed = match &tui.installed {
+        Some(InstalledMod::Unknown) => {
+            "Unknown, the game files don't match any of your mods".to_string()
+        }
+        Some(installed) => installed.to_string(),
+        None => tui
+            .mod_manager
+            .current_mod()
+            .map_or("Uncertain...".to_string(), |current_mod| {
+                current_mod.name().to_string()
+            }),
+    };
+    let paths_config = tui.mod_manager.configs().paths_config();
+
+    let lines = vec![
+        Line::from(vec!["Active mod: ".bold(), installed.into()]),
+        path_line(paths_config.game_path()),
+        path_line(paths_config.mods_path()),
+        path_line(paths_config.mods_group_path()),
+    ];
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Status ")),
+        area,
+    );
+}
+
+fn path_line<P: ProgramPath>(path: &P) -> Line<'static> {
+    Line::from(vec![
+        format!("{}: ", P::key()).bold(),
+        path.path().display().to_string().into(),
+    ])
+}
+
+fn draw_status(frame: &mut Frame, tui: &Tui, area: Rect) {
+    let line = match &tui.status {
+        Status::Idle => Line::default(),
+        Status::Info(message) => Line::from(message.as_str()),
+        Status::Error(message) => Line::from(message.as_str().red()),
+    };
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn keys_line(keys: &[(&'static str, &'static str)]) -> Line<'static> {
+    let mut spans = Vec::with_capacity(keys.len() * 2);
+    for (key, action) in keys {
+        spans.push(Span::from(format!(" {key} ")).reversed());
+        spans.push(Span::from(format!(" {action}  ")));
+    }
+
+    Line::from(spans)
+}
+
+fn draw_popup(frame: &mut Frame, popup: &Popup) {
+    let (title, paragraph, keys, height) = match popup {
+        Popup::Confirm {
+            title,
+            text,
+            scroll,
+            ..
+        } => (
+            title.as_str(),
+            Paragraph::new(text.as_str()).scroll((*scroll, 0)),
+            keys_line(&[("y", "apply"), ("n", "cancel"), ("↑↓", "scroll")]),
+            Constraint::Percentage(70),
+        ),
+        Popup::Report {
+            title,
+            text,
+            scroll,
+        } => (
+            title.as_str(),
+            Paragraph::new(text.as_str()).scroll((*scroll, 0)),
+            keys_line(&[("enter", "close"), ("↑↓", "scroll")]),
+            Constraint::Percentage(70),
+        ),
+        Popup::NewMod { name } => (
+            "Create a new mod folder",
+            Paragraph::new(vec![
+                Line::from("What will be the mod's name? (Please use the snake case identifier)"),
+                Line::from(format!("> {name}_")),
+            ]),
+            keys_line(&[("enter", "create"), ("esc", "cancel")]),
+            Constraint::Length(4),
+        ),
+    };
+
+    let [area] = Layout::vertical([height])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Percentage(80)])
+        .flex(Flex::Center)
+        .areas(area);
+    let block = Block::bordered()
+        .title(format!(" {title} "))
+        .title_bottom(keys);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph.wrap(Wrap { trim: false }).block(block), area);
+}
@@ -0,0 +1,485 @@
+mod details;
+mod draw;
+
+use std::{io, time::Duration};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{text::Text, widgets::ListState, DefaultTerminal};
+
+use crate::{
+    fingerprint::InstalledMod,
+    hotline_mod::{HotlineMod, HotlineModName},
+    mod_manager::ModManager,
+    replace_mod::plan::SwitchPlan,
+    run_game::run_hotline_miami_2,
+};
+
+/// The keys of the library, as shown at the bottom of the screen.
+const KEYS: &[(&str, &str)] = &[
+    ("enter", "switch"),
+    ("d", "default"),
+    ("g", "run game"),
+    ("c", "create"),
+    ("r", "rescan"),
+    ("q", "quit"),
+];
+
+/// A full-screen interface with the mod library, the details of the highlighted mod and the one
+/// installed, running the same operations as the menu.
+pub struct Tui {
+    mod_manager: ModManager,
+    installed: Option<InstalledMod>,
+    /// Indices of the mods in the library, in the order they're listed.
+    library: Vec<usize>,
+    selected: ListState,
+    details: Option<(usize, Text<'static>)>,
+    popup: Option<Popup>,
+    status: Status,
+    quit: bool,
+}
+
+enum Popup {
+    /// A switch waiting for the user to apply it.
+    Confirm {
+        title: String,
+        text: String,
+        switch: PendingSwitch,
+        scroll: u16,
+    },
+    /// Something to read before going back to the library.
+    Report {
+        title: String,
+        text: String,
+        scroll: u16,
+    },
+    /// The folder name of the mod to create.
+    NewMod { name: String },
+}
+
+enum PendingSwitch {
+    Mod(HotlineMod),
+    Default,
+}
+
+/// The line under the status bar, telling how the last operation went.
+enum Status {
+    Idle,
+    Info(String),
+    Error(String),
+}
+
+impl Tui {
+    pub fn new(mod_manager: ModManager) -> Self {
+        let mut tui = Tui {
+            mod_manager,
+            installed: None,
+            library: Vec::new(),
+            selected: ListState::default(),
+            details: None,
+            popup: None,
+            status: Status::Idle,
+            quit: false,
+        };
+        tui.list_library(None);
+
+        if tui.mod_manager.configs().settings().watch_mods()
+            && let Err(err) = tui.mod_manager.watch_mods()
+        {
+            tui.status = Status::Error(err.to_string());
+        }
+
+        tui
+    }
+
+    /// Takes over the terminal until the user quits, putting it back even after an error.
+    pub fn run(mut self) -> io::Result<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.busy(terminal, "Looking at the game files...")?;
+        self.status = Status::Idle;
+        self.detect_installed_mod();
+
+        while !self.quit {
+            self.draw(terminal)?;
+
+            // Waits for a key a little at a time, to notice the changes the watcher saw.
+            if !event::poll(REFRESH_INTERVAL)? {
+                self.refresh_mods();
+                continue;
+            }
+            if let Event::Key(key) = event::read()?
+                && key.kind != KeyEventKind::Release
+            {
+                self.handle_key(key, terminal)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.load_details();
+        terminal.draw(|frame| draw::draw(frame, self))?;
+
+        Ok(())
+    }
+
+    /// Shows what's being done before an operation that can take a while.
+    fn busy(&mut self, terminal: &mut DefaultTerminal, message: &str) -> io::Result<()> {
+        self.status = Status::Info(message.to_string());
+        self.draw(terminal)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return Ok(());
+        }
+
+        match self.popup.take() {
+            Some(popup) => self.handle_popup_key(popup, key, terminal),
+            None => self.handle_library_key(key, terminal),
+        }
+    }
+
+    fn handle_library_key(
+        &mut self,
+        key: KeyEvent,
+        terminal: &mut DefaultTerminal,
+    ) -> io::Result<()> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+            KeyCode::PageUp => self.selected.scroll_up_by(PAGE_SIZE),
+            KeyCode::PageDown => self.selected.scroll_down_by(PAGE_SIZE),
+            KeyCode::Home => self.selected.select_first(),
+            KeyCode::End => self.selected.select_last(),
+            KeyCode::Enter | KeyCode::Char('s') => self.plan_switch(terminal)?,
+            KeyCode::Char('d') => self.plan_default_settings(terminal)?,
+            KeyCode::Char('g') => self.run_game(),
+            KeyCode::Char('c') => {
+                self.popup = Some(Popup::NewMod {
+                    name: String::new(),
+                });
+            }
+            KeyCode::Char('r') => self.rescan(terminal)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_popup_key(
+        &mut self,
+        popup: Popup,
+        key: KeyEvent,
+        terminal: &mut DefaultTerminal,
+    ) -> io::Result<()> {
+        self.popup = match popup {
+            Popup::Confirm { switch, .. }
+                if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) =>
+            {
+                return self.apply_switch(&switch, terminal);
+            }
+            Popup::Confirm { .. } if matches!(key.code, KeyCode::Char('n') | KeyCode::Esc) => None,
+            Popup::Report { .. }
+                if matches!(key.code, KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q')) =>
+            {
+                None
+            }
+            Popup::Confirm {
+                title,
+                text,
+                switch,
+                scroll,
+            } => Some(Popup::Confirm {
+                scroll: scrolled(scroll, key.code),
+                title,
+                text,
+                switch,
+            }),
+            Popup::Report {
+                title,
+                text,
+                scroll,
+            } => Some(Popup::Report {
+                scroll: scrolled(scroll, key.code),
+                title,
+                text,
+            }),
+            Popup::NewMod { name } if key.code == KeyCode::Enter => {
+                self.create_mod_folder(name.trim());
+                None
+            }
+            Popup::NewMod { .. } if key.code == KeyCode::Esc => None,
+            Popup::NewMod { mut name } => {
+                match key.code {
+                    KeyCode::Backspace => {
+                        name.pop();
+                    }
+                    KeyCode::Char(character) => name.push(character),
+                    _ => {}
+                }
+                Some(Popup::NewMod { name })
+            }
+        };
+
+        Ok(())
+    }
+
+    fn plan_switch(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let Some(desired_mod) = self.selected_mod().cloned() else {
+            self.status = Status::Error("You have no mods in your folder right now. Try downloading new mods or bringing your existing mods to this folder.".to_string());
+            return Ok(());
+        };
+
+        self.busy(
+            terminal,
+            &format!("Planning the switch to {desired_mod}..."),
+        )?;
+        match self.mod_manager.plan_switch(&desired_mod) {
+            Ok(plan) => {
+                self.status = Status::Idle;
+                self.popup = Some(switch_popup(
+                    format!("Switch to {desired_mod}"),
+                    &plan,
+                    PendingSwitch::Mod(desired_mod),
+                ));
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+
+        Ok(())
+    }
+
+    fn plan_default_settings(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.busy(terminal, "Planning the switch to the default settings...")?;
+        match self.mod_manager.plan_default_settings() {
+            Ok(plan) => {
+                self.status = Status::Idle;
+                self.popup = Some(switch_popup(
+                    "Use the default settings".to_string(),
+                    &plan,
+                    PendingSwitch::Default,
+                ));
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+
+        Ok(())
+    }
+
+    fn apply_switch(
+        &mut self,
+        switch: &PendingSwitch,
+        terminal: &mut DefaultTerminal,
+    ) -> io::Result<()> {
+        self.busy(terminal, "Switching...")?;
+        let result = match switch {
+            PendingSwitch::Mod(desired_mod) => self.mod_manager.switch_mod(desired_mod),
+            PendingSwitch::Default => self.mod_manager.use_default_settings(),
+        };
+        // The copies draw their progress bars straight to the terminal, over the screen, so the
+        // next draw has to repaint all of it.
+        terminal.clear()?;
+
+        match result {
+            Ok(report) => {
+                let warnings = report.warnings();
+                if !warnings.is_empty() {
+                    self.popup = Some(Popup::Report {
+                        title: "Switched, with warnings".to_string(),
+                        text: warnings.join("\n"),
+                        scroll: 0,
+                    });
+                }
+                self.status = Status::Info(match switch {
+                    PendingSwitch::Mod(desired_mod) => format!("Switched to {desired_mod}."),
+                    PendingSwitch::Default => "Switched to the default settings.".to_string(),
+                });
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+
+        // The last used time of the mod changed, and so did the game files.
+        self.details = None;
+        self.detect_installed_mod();
+
+        Ok(())
+    }
+
+    fn run_game(&mut self) {
+        self.status = match run_hotline_miami_2() {
+            Ok(()) => Status::Info("Hotline Miami 2 is starting through Steam.".to_string()),
+            Err(err) => Status::Error(err.to_string()),
+        };
+    }
+
+    fn create_mod_folder(&mut self, name: &str) {
+        if name.is_empty() {
+            return;
+        }
+
+        match self.mod_manager.create_mod_folder(name) {
+            Ok(new_mod) => {
+                let new_mod = new_mod.name().clone();
+                self.status = Status::Info(format!("Created the folder of {new_mod}."));
+                self.list_library(Some(&new_mod));
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    /// Reads the mods group folder and the game files again.
+    fn rescan(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.busy(terminal, "Reading your mods again...")?;
+        let selected = self.selected_mod().map(|hm_mod| hm_mod.name().clone());
+
+        match self.mod_manager.rescan_mods() {
+            Ok(all_mods) => {
+                self.status = Status::Info(format!("Found {} mods.", all_mods.mods().len()));
+                self.list_library(selected.as_ref());
+                self.detect_installed_mod();
+            }
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+
+        Ok(())
+    }
+
+    /// Picks up the changes the watcher saw in the mods group folder.
+    fn refresh_mods(&mut self) {
+        let selected = self.selected_mod().map(|hm_mod| hm_mod.name().clone());
+
+        match self.mod_manager.refresh_mods() {
+            Ok(true) => {
+                self.status = Status::Info(format!(
+                    "Your mods folder changed, found {} mods.",
+                    self.mod_manager.all_mods().mods().len()
+                ));
+                self.list_library(selected.as_ref());
+                self.detect_installed_mod();
+            }
+            Ok(false) => {}
+            Err(err) => self.status = Status::Error(err.to_string()),
+        }
+    }
+
+    fn detect_installed_mod(&mut self) {
+        match self.mod_manager.detect_installed_mod() {
+            Ok(installed) => self.installed = Some(installed),
+            Err(err) => {
+                self.installed = None;
+                self.status = Status::Error(err.to_string());
+            }
+        }
+    }
+
+    /// Lists the mods by name, highlighting `selected` when it's one of them.
+    fn list_library(&mut self, selected: Option<&HotlineModName>) {
+        let mods = self.mod_manager.all_mods().mods();
+
+        self.library = (0..mods.len()).collect();
+        self.library
+            .sort_by_cached_key(|index| mods[*index].to_string().to_lowercase());
+        self.details = None;
+
+        let position = selected
+            .and_then(|name| {
+                self.library
+                    .iter()
+                    .position(|index| mods[*index].name() == name)
+            })
+            .or((!self.library.is_empty()).then_some(0));
+        self.selected.select(position);
+    }
+
+    fn selected_mod(&self) -> Option<&HotlineMod> {
+        let index = self.library.get(self.selected.selected()?)?;
+
+        self.mod_manager.all_mods().mods().get(*index)
+    }
+
+    /// Reads the details of the highlighted mod, unless they already were.
+    fn load_details(&mut self) {
+        // Moving past the last mod leaves it highlighted.
+        if self
+            .selected
+            .selected()
+            .is_some_and(|position| position >= self.library.len())
+        {
+            self.selected.select(self.library.len().checked_sub(1));
+        }
+
+        let Some(index) = self
+            .selected
+            .selected()
+            .and_then(|position| self.library.get(position))
+            .copied()
+        else {
+            self.details = None;
+            return;
+        };
+        if self
+            .details
+            .as_ref()
+            .is_some_and(|(loaded, _)| *loaded == index)
+        {
+            return;
+        }
+
+        let hm_mod = &self.mod_manager.all_mods().mods()[index];
+        let details = details::mod_details(
+            hm_mod,
+            &self.mod_manager.mod_dir(hm_mod),
+            self.mod_manager.configs().last_used(hm_mod.name()),
+        );
+        self.details = Some((index, details));
+    }
+}
+
+const PAGE_SIZE: u16 = 10;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Asks to apply the plan, unless something stops it from being applied.
+fn switch_popup(title: String, plan: &SwitchPlan, switch: PendingSwitch) -> Popup {
+    let text = plan.to_string();
+
+    if plan.blocked_by().is_some() {
+        Popup::Report {
+            title,
+            text: format!("{text}Move the file out of your mods folder and try again."),
+            scroll: 0,
+        }
+    } else if !plan.corrupt().is_empty() {
+        Popup::Report {
+            title,
+            text: format!("{text}Download the mod again and try again."),
+            scroll: 0,
+        }
+    } else {
+        Popup::Confirm {
+            title,
+            text,
+            switch,
+            scroll: 0,
+        }
+    }
+}
+
+fn scrolled(scroll: u16, key: KeyCode) -> u16 {
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => scroll.saturating_add(1),
+        KeyCode::PageUp => scroll.saturating_sub(PAGE_SIZE),
+        KeyCode::PageDown => scroll.saturating_add(PAGE_SIZE),
+        _ => scroll,
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    configs::paths_config::{GamePath, ModsPath, ProgramPath},
+    file_hash::{FileHash, HashCache},
+    replace_mod::{
+        replace_mods::{installed_patchwads, ReplaceModError},
+        replace_music::game_music_path,
+    },
+};
+
+/// Whether the game files are exactly the ones the game ships with.
+#[derive(Debug, Clone)]
+pub struct VanillaReport {
+    music: MusicCheck,
+    patchwads: Vec<PathBuf>,
+}
+
+/// How the installed music compares to the original.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MusicCheck {
+    Original(FileHash),
+    Replaced {
+        found: FileHash,
+        expected: FileHash,
+    },
+    Missing,
+    /// The original music was never vaulted, so there's nothing to compare with.
+    NoReference,
+}
+
+impl VanillaReport {
+    /// Checks that the mods folder has no patchwads and that the game music has the hash of the
+    /// original, when it's known.
+    pub fn check(
+        game_path: &GamePath,
+        mods_path: &ModsPath,
+        original: Option<&FileHash>,
+        cache: &mut HashCache,
+    ) -> Result<Self, VanillaError> {
+        let installed_music = game_music_path(game_path);
+
+        let music = match original {
+            None => MusicCheck::NoReference,
+            Some(_) if !installed_music.is_file() => MusicCheck::Missing,
+            Some(expected) => {
+                let found = cache
+                    .hash(&installed_music)
+                    .map_err(VanillaError::Hashing)?;
+                if found == *expected {
+                    MusicCheck::Original(found)
+                } else {
+                    MusicCheck::Replaced {
+                        found,
+                        expected: expected.clone(),
+                    }
+                }
+            }
+        };
+
+        Ok(VanillaReport {
+            music,
+            patchwads: installed_patchwads(mods_path)?,
+        })
+    }
+
+    pub fn is_vanilla(&self) -> bool {
+        matches!(self.music, MusicCheck::Original(_)) && self.patchwads.is_empty()
+    }
+
+    pub fn music(&self) -> &MusicCheck {
+        &self.music
+    }
+
+    /// The patchwads in the mods folder, none of which a vanilla game has.
+    pub fn patchwads(&self) -> &[PathBuf] {
+        &self.patchwads
+    }
+}
+
+impl fmt::Display for VanillaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = if self.is_vanilla() { "PASS" } else { "FAIL" };
+        writeln!(f, "Vanilla check: {result}")?;
+
+        match &self.music {
+            MusicCheck::Original(sha256) => writeln!(f, "  Music: original (sha256 {sha256})")?,
+            MusicCheck::Replaced { found, expected } => writeln!(
+                f,
+                "  Music: replaced (sha256 {found}, the original is {expected})"
+            )?,
+            MusicCheck::Missing => writeln!(f, "  Music: missing")?,
+            MusicCheck::NoReference => writeln!(
+                f,
+                "  Music: can't be checked, the original music was never vaulted"
+            )?,
+        }
+
+        if self.patchwads.is_empty() {
+            writeln!(f, "  Patchwads: none")?;
+        }
+        for path in &self.patchwads {
+            writeln!(f, "  Patchwad: {}", path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves the patchwads out of the mods folder into a folder inside it the game doesn't load,
+/// returning where each one went.
+pub fn set_aside_patchwads(mods_path: &ModsPath) -> Result<Vec<PathBuf>, VanillaError> {
+    let set_aside_dir = set_aside_dir(mods_path.path());
+    let mut moved = Vec::new();
+
+    for patchwad in installed_patchwads(mods_path)? {
+        let Some(file_name) = patchwad.file_name() else {
+            continue;
+        };
+        let destination = available_path(&set_aside_dir.join(file_name));
+
+        fs::create_dir_all(&set_aside_dir)
+            .and_then(|()| fs::rename(&patchwad, &destination))
+            .map_err(|err| VanillaError::SettingAside(err, patchwad.into()))?;
+        moved.push(destination);
+    }
+
+    Ok(moved)
+}
+
+pub fn set_aside_dir(dir: &Path) -> PathBuf {
+    dir.join(SET_ASIDE_DIR_NAME)
+}
+
+/// Adds a number to the file name until nothing has that name, so nothing set aside before gets
+/// overwritten.
+fn available_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+    let mut candidate = path.to_path_buf();
+    let mut number = 1;
+    while fs::symlink_metadata(&candidate).is_ok() {
+        candidate = path.with_file_name(format!("{stem} ({number}).{extension}"));
+        number += 1;
+    }
+
+    candidate
+}
+
+#[derive(Error, Debug)]
+pub enum VanillaError {
+    #[error("{0}")]
+    ReadingModsDirectory(#[from] ReplaceModError),
+    #[error("Error reading the game music to check it. Error: {0}")]
+    Hashing(io::Error),
+    #[error("Error moving {} out of your mods folder. Error: {0}", .1.display())]
+    SettingAside(io::Error, Box<Path>),
+}
+
+const SET_ASIDE_DIR_NAME: &str = ".hm_mod_manager_set_aside";
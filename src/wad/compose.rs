@@ -0,0 +1,298 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    merge::merge_directories, validate::validate_wad, write::write_wad_file, Wad, WadDirectory,
+    WadEntry, WadError,
+};
+
+/// A file of the composed wad that doesn't come from the base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackOverride {
+    track: String,
+    source: TrackSource,
+}
+
+/// Where the data of an overridden file comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackSource {
+    /// A file of another wad, which can have a different name there.
+    Wad { path: PathBuf, track: String },
+    /// A loose file, like an `.ogg` track.
+    File(PathBuf),
+}
+
+impl TrackOverride {
+    pub fn new(track: String, source: TrackSource) -> Self {
+        TrackOverride { track, source }
+    }
+
+    /// The name of the file in the composed wad, like `Music/Hotline.ogg`.
+    pub fn track(&self) -> &str {
+        &self.track
+    }
+
+    pub fn source(&self) -> &TrackSource {
+        &self.source
+    }
+}
+
+/// Where the bytes of one file of the composed wad are read from.
+enum Chunk<'a> {
+    /// An entry of one of the source wads, by their positions.
+    Wad(usize, usize),
+    File(&'a Path),
+}
+
+/// The tables of a composed wad, and where the bytes of each of its files come from.
+struct Composition<'a> {
+    sources: Vec<(&'a Path, Wad)>,
+    chunks: Vec<(Chunk<'a>, u64)>,
+    wad: Wad,
+}
+
+/// Writes a copy of the `base` wad to `output` with some of its files taken from elsewhere.
+///
+/// The files keep the order of the base, and overrides of files the base doesn't have are added
+/// after them. When several overrides name the same file, the last one wins.
+pub fn compose_wad(
+    base: &Path,
+    overrides: &[TrackOverride],
+    output: &Path,
+) -> Result<Wad, WadError> {
+    let composition = Composition::new(base, overrides)?;
+    write_wad_file(output, |writer| composition.write(writer))?;
+
+    Ok(composition.wad)
+}
+
+/// The size of the wad [`compose_wad`] writes, worked out without writing it.
+pub fn composed_len(base: &Path, overrides: &[TrackOverride]) -> Result<u64, WadError> {
+    let composition = Composition::new(base, overrides)?;
+
+    Ok(
+        composition.wad.data_offset()
+            + composition.chunks.iter().map(|(_, size)| size).sum::<u64>(),
+    )
+}
+
+impl<'a> Composition<'a> {
+    fn new(base: &'a Path, overrides: &'a [TrackOverride]) -> Result<Self, WadError> {
+        let mut sources = vec![(base, validate_wad(base)?)];
+        for track_override in overrides {
+            if let TrackSource::Wad { path, .. } = &track_override.source
+                && !sources.iter().any(|(source, _)| source == path)
+            {
+                sources.push((path, validate_wad(path)?));
+            }
+        }
+
+        let mut names = sources[0]
+            .1
+            .entries()
+            .iter()
+            .map(|entry| entry.name().to_string())
+            .collect::<Vec<_>>();
+        let mut added = Vec::new();
+        for track_override in overrides {
+            if !names.contains(&track_override.track) {
+                names.push(track_override.track.clone());
+                added.push(track_override.track.as_str());
+            }
+        }
+
+        let mut chunks = Vec::with_capacity(names.len());
+        let mut entries = Vec::with_capacity(names.len());
+        let mut offset = 0;
+        for name in &names {
+            let (chunk, size) = match overrides.iter().rfind(|track| track.track == *name) {
+                Some(TrackOverride {
+                    source: TrackSource::File(path),
+                    ..
+                }) => {
+                    let size = fs::metadata(path)
+                        .map_err(|err| WadError::ReadingFile(err, path.as_path().into()))?
+                        .len();
+                    (Chunk::File(path), size)
+                }
+                Some(TrackOverride {
+                    source: TrackSource::Wad { path, track },
+                    ..
+                }) => {
+                    let index = sources
+                        .iter()
+                        .position(|(source, _)| source == path)
+                        .expect("Every source wad was read above");
+                    let entry = sources[index]
+                        .1
+                        .entries()
+                        .iter()
+                        .position(|entry| entry.name() == track)
+                        .ok_or_else(|| {
+                            WadError::MissingEntry(track.clone(), path.as_path().into())
+                        })?;
+                    (
+                        Chunk::Wad(index, entry),
+                        sources[index].1.entries()[entry].size(),
+                    )
+                }
+                None => {
+                    let entry = sources[0]
+                        .1
+                        .entries()
+                        .iter()
+                        .position(|entry| entry.name() == name)
+                        .expect("Names without overrides are the base's");
+                    (Chunk::Wad(0, entry), sources[0].1.entries()[entry].size())
+                }
+            };
+
+            chunks.push((chunk, size));
+            entries.push(WadEntry::new(name.clone(), size, offset));
+            offset += size;
+        }
+
+        let added_listings = WadDirectory::listings_for(added);
+        let wad = Wad::new(
+            sources[0].1.version(),
+            sources[0].1.extra_header().to_vec(),
+            entries,
+            merge_directories(sources[0].1.directories().iter().chain(&added_listings)),
+        );
+
+        Ok(Composition {
+            sources,
+            chunks,
+            wad,
+        })
+    }
+
+    fn write(&self, writer: &mut impl Write) -> Result<(), WadError> {
+        self.wad.write_tables(writer)?;
+
+        let mut files = self
+            .sources
+            .iter()
+            .map(|(path, _)| File::open(path))
+            .collect::<io::Result<Vec<_>>>()?;
+        for (chunk, size) in &self.chunks {
+            match chunk {
+                Chunk::Wad(index, entry) => {
+                    let source = &self.sources[*index].1;
+                    source.copy_entry(&mut files[*index], &source.entries()[*entry], writer)?;
+                }
+                Chunk::File(path) => {
+                    let reading_error = |err| WadError::ReadingFile(err, (*path).into());
+                    let file = File::open(path).map_err(reading_error)?;
+                    if io::copy(&mut file.take(*size), writer).map_err(reading_error)? != *size {
+                        return Err(reading_error(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wad::WAD_VERSION;
+
+    use super::*;
+
+    /// Writes a wad at `path` holding the given files, with directory listings for them.
+    fn write_wad(path: &Path, files: &[(&str, &[u8])]) {
+        let mut offset = 0;
+        let entries = files
+            .iter()
+            .map(|(name, data)| {
+                let entry = WadEntry::new((*name).to_string(), data.len() as u64, offset);
+                offset += data.len() as u64;
+                entry
+            })
+            .collect();
+        let directories = WadDirectory::listings_for(files.iter().map(|(name, _)| *name));
+        let wad = Wad::new(WAD_VERSION, vec![0xAB], entries, directories);
+
+        let mut bytes = Vec::new();
+        wad.write_tables(&mut bytes).unwrap();
+        for (_, data) in files {
+            bytes.extend(*data);
+        }
+        fs::write(path, bytes).unwrap();
+    }
+
+    fn read_file(wad_path: &Path, name: &str) -> Vec<u8> {
+        let wad = Wad::open(wad_path).unwrap();
+        let entry = wad.find(name).unwrap();
+        wad.read_entry(&mut File::open(wad_path).unwrap(), entry)
+            .unwrap()
+    }
+
+    #[test]
+    fn composed_len_matches_the_written_wad() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.wad");
+        let other = dir.path().join("other.wad");
+        let track = dir.path().join("track.ogg");
+        let output = dir.path().join("composed.wad");
+        write_wad(
+            &base,
+            &[("Music/Intro.ogg", b"intro"), ("Music/Outro.ogg", b"outro")],
+        );
+        write_wad(&other, &[("Other/Theme.ogg", b"other theme")]);
+        fs::write(&track, b"loose track").unwrap();
+        let overrides = [
+            TrackOverride::new(
+                "Music/Intro.ogg".to_string(),
+                TrackSource::Wad {
+                    path: other.clone(),
+                    track: "Other/Theme.ogg".to_string(),
+                },
+            ),
+            TrackOverride::new("Extra/New.ogg".to_string(), TrackSource::File(track)),
+        ];
+
+        let len = composed_len(&base, &overrides).unwrap();
+        let wad = compose_wad(&base, &overrides, &output).unwrap();
+
+        assert_eq!(len, fs::metadata(&output).unwrap().len());
+        assert_eq!(wad, Wad::open(&output).unwrap());
+        assert_eq!(wad.extra_header(), [0xAB]);
+        let names = wad.entries().iter().map(WadEntry::name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["Music/Intro.ogg", "Music/Outro.ogg", "Extra/New.ogg"]
+        );
+        assert_eq!(read_file(&output, "Music/Intro.ogg"), b"other theme");
+        assert_eq!(read_file(&output, "Music/Outro.ogg"), b"outro");
+        assert_eq!(read_file(&output, "Extra/New.ogg"), b"loose track");
+    }
+
+    #[test]
+    fn the_last_override_of_a_file_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.wad");
+        let first = dir.path().join("first.ogg");
+        let last = dir.path().join("last.ogg");
+        let output = dir.path().join("composed.wad");
+        write_wad(&base, &[("Music/Intro.ogg", b"intro")]);
+        fs::write(&first, b"first").unwrap();
+        fs::write(&last, b"the last one").unwrap();
+        let overrides = [
+            TrackOverride::new("Music/Intro.ogg".to_string(), TrackSource::File(first)),
+            TrackOverride::new("Music/Intro.ogg".to_string(), TrackSource::File(last)),
+        ];
+
+        let len = composed_len(&base, &overrides).unwrap();
+        let wad = compose_wad(&base, &overrides, &output).unwrap();
+
+        assert_eq!(len, fs::metadata(&output).unwrap().len());
+        assert_eq!(wad.entries().len(), 1);
+        assert_eq!(read_file(&output, "Music/Intro.ogg"), b"the last one");
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use super::Wad;
+
+/// Assets provided by more than one patchwad in the mods folder.
+#[derive(Debug, Clone, Default)]
+pub struct ConflictReport {
+    conflicts: Vec<AssetConflict>,
+}
+
+/// An asset path and every patchwad that provides it, in the order the game loads them.
+#[derive(Debug, Clone)]
+pub struct AssetConflict {
+    asset: String,
+    providers: Vec<PathBuf>,
+}
+
+impl ConflictReport {
+    /// Reads the file table of every patchwad and lists the assets more than one of them
+    /// provides. Patchwads that can't be read are left out, validation reports those.
+    pub fn find<'a>(patchwads: impl IntoIterator<Item = &'a Path>) -> Self {
+        let mut patchwads = patchwads.into_iter().collect::<Vec<_>>();
+        sort_by_load_order(&mut patchwads);
+
+        Self::in_order(patchwads)
+    }
+
+    /// Like [`ConflictReport::find`], with the patchwads already in the order they're loaded.
+    pub fn in_order<'a>(patchwads: impl IntoIterator<Item = &'a Path>) -> Self {
+        let mut providers = BTreeMap::<String, Vec<PathBuf>>::new();
+        for patchwad in patchwads {
+            let Ok(wad) = Wad::open(patchwad) else {
+                continue;
+            };
+            for entry in wad.entries() {
+                providers
+                    .entry(entry.name().to_string())
+                    .or_default()
+                    .push(patchwad.to_path_buf());
+            }
+        }
+
+        ConflictReport {
+            conflicts: providers
+                .into_iter()
+                .filter(|(_, providers)| providers.len() > 1)
+                .map(|(asset, providers)| AssetConflict { asset, providers })
+                .collect(),
+        }
+    }
+
+    pub fn conflicts(&self) -> &[AssetConflict] {
+        &self.conflicts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl AssetConflict {
+    /// The path of the asset inside the patchwads, like `Sprites/sprHero.png`.
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
+    pub fn providers(&self) -> &[PathBuf] {
+        &self.providers
+    }
+
+    /// The patchwad whose copy of the asset the game ends up using.
+    pub fn winner(&self) -> &Path {
+        self.providers
+            .last()
+            .expect("A conflict has at least two providers")
+    }
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for conflict in &self.conflicts {
+            writeln!(f, "  Conflict: {conflict}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for AssetConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let providers = self
+            .providers
+            .iter()
+            .map(|path| file_name(path))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(
+            f,
+            "{} is in {providers}, the one in {} wins",
+            self.asset,
+            file_name(self.winner())
+        )
+    }
+}
+
+/// The game loads patchwads in name order, and a later patchwad overrides the assets of an
+/// earlier one.
+pub fn sort_by_load_order(patchwads: &mut [&Path]) {
+    patchwads.sort_by_key(|path| {
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_lowercase()
+    });
+}
+
+fn file_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::wad::{WadEntry, WAD_VERSION};
+
+    use super::*;
+
+    /// Writes a patchwad at `path` that provides the given assets, each one byte long.
+    fn write_patchwad(path: &Path, assets: &[&str]) {
+        let entries = (0u64..)
+            .zip(assets)
+            .map(|(offset, asset)| WadEntry::new((*asset).to_string(), 1, offset))
+            .collect();
+        let wad = Wad::new(WAD_VERSION, Vec::new(), entries, Vec::new());
+
+        let mut bytes = Vec::new();
+        wad.write_tables(&mut bytes).unwrap();
+        bytes.extend(vec![0; assets.len()]);
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn sorts_by_name_ignoring_case() {
+        let mut patchwads = [
+            Path::new("mods/b.patchwad"),
+            Path::new("mods/C.patchwad"),
+            Path::new("other/A.patchwad"),
+        ];
+
+        sort_by_load_order(&mut patchwads);
+
+        assert_eq!(
+            patchwads,
+            [
+                Path::new("other/A.patchwad"),
+                Path::new("mods/b.patchwad"),
+                Path::new("mods/C.patchwad"),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_last_patchwad_loaded_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let early = dir.path().join("a_early.patchwad");
+        let late = dir.path().join("B_late.patchwad");
+        let alone = dir.path().join("c_alone.patchwad");
+        write_patchwad(&early, &["Sprites/Hero.png", "Sounds/Shot.wav"]);
+        write_patchwad(&late, &["Sprites/Hero.png"]);
+        write_patchwad(&alone, &["Sprites/Enemy.png"]);
+
+        let report = ConflictReport::find([late.as_path(), alone.as_path(), early.as_path()]);
+
+        let [conflict] = report.conflicts() else {
+            panic!("Expected one conflict, found {report:?}");
+        };
+        assert_eq!(conflict.asset(), "Sprites/Hero.png");
+        assert_eq!(conflict.providers(), [early.clone(), late.clone()]);
+        assert_eq!(conflict.winner(), late);
+    }
+
+    #[test]
+    fn keeps_the_given_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("z.patchwad");
+        let second = dir.path().join("a.patchwad");
+        write_patchwad(&first, &["Sprites/Hero.png"]);
+        write_patchwad(&second, &["Sprites/Hero.png"]);
+
+        let report = ConflictReport::in_order([first.as_path(), second.as_path()]);
+
+        assert_eq!(report.conflicts()[0].winner(), second);
+    }
+}
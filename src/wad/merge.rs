@@ -0,0 +1,90 @@
+use std::{collections::HashMap, fs::File, io, path::Path};
+
+use super::{
+    validate::validate_wad, write::write_wad_file, Wad, WadDirectory, WadEntry, WadError,
+    WAD_VERSION,
+};
+
+/// Merges the wads into one at `output`.
+///
+/// The wads are given in load order: when several provide the same asset, the last one wins,
+/// like in the game. The files keep the order they first appear in, and the directory listings
+/// of every wad are combined.
+pub fn merge_wads(wads: &[&Path], output: &Path) -> Result<Wad, WadError> {
+    let sources = wads
+        .iter()
+        .map(|path| validate_wad(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut names = Vec::new();
+    let mut winners = HashMap::new();
+    for (source_index, source) in sources.iter().enumerate() {
+        for entry in source.entries() {
+            if winners
+                .insert(entry.name().to_string(), (source_index, entry))
+                .is_none()
+            {
+                names.push(entry.name().to_string());
+            }
+        }
+    }
+
+    let mut offset = 0;
+    let entries = names
+        .iter()
+        .map(|name| {
+            let size = winners[name].1.size();
+            let entry = WadEntry::new(name.clone(), size, offset);
+            offset += size;
+            entry
+        })
+        .collect();
+    let merged = Wad::new(
+        WAD_VERSION,
+        Vec::new(),
+        entries,
+        merge_directories(sources.iter().flat_map(Wad::directories)),
+    );
+
+    write_wad_file(output, |writer| {
+        merged.write_tables(writer)?;
+
+        let mut files = wads
+            .iter()
+            .map(File::open)
+            .collect::<io::Result<Vec<_>>>()?;
+        for name in &names {
+            let (source_index, entry) = winners[name];
+            sources[source_index].copy_entry(&mut files[source_index], entry, writer)?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(merged)
+}
+
+/// Every directory of every listing, each with the children all of them list.
+pub(super) fn merge_directories<'a>(
+    listings: impl IntoIterator<Item = &'a WadDirectory>,
+) -> Vec<WadDirectory> {
+    let mut directories = Vec::<WadDirectory>::new();
+
+    for directory in listings {
+        match directories
+            .iter_mut()
+            .find(|merged| merged.name == directory.name)
+        {
+            Some(merged) => {
+                for child in &directory.children {
+                    if !merged.children.contains(child) {
+                        merged.children.push(child.clone());
+                    }
+                }
+            }
+            None => directories.push(directory.clone()),
+        }
+    }
+
+    directories
+}
@@ -0,0 +1,518 @@
+//! The container format of the game's `.wad` and `.patchwad` files.
+//!
+//! Every number is little endian, and every string is a `u32` length followed by that many bytes.
+//!
+//! ```text
+//! "AGAR"                      magic
+//! u32 major, u32 minor        format version, 1.1 in every known file
+//! u32 extra, [u8; extra]      extra header bytes, usually none
+//! u32 file count              file table
+//!     string name, u64 size, u64 offset
+//! u32 directory count         directory table
+//!     string name, u32 child count
+//!         string name, u8 kind (0 for a file, 1 for a directory)
+//! file data                   offsets are counted from here
+//! ```
+pub mod compose;
+pub mod conflicts;
+pub mod merge;
+pub mod unpack;
+pub mod validate;
+pub mod write;
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub const WAD_MAGIC: &[u8; 4] = b"AGAR";
+pub const WAD_VERSION: (u32, u32) = (1, 1);
+
+/// The header and tables of a wad file, without the file data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wad {
+    version: (u32, u32),
+    extra_header: Vec<u8>,
+    entries: Vec<WadEntry>,
+    directories: Vec<WadDirectory>,
+    data_offset: u64,
+}
+
+/// A file stored in a wad.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WadEntry {
+    name: String,
+    size: u64,
+    offset: u64,
+}
+
+/// A directory listing stored in a wad, which the game uses to browse the files.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WadDirectory {
+    name: String,
+    children: Vec<WadDirectoryChild>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WadDirectoryChild {
+    name: String,
+    kind: ChildKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildKind {
+    File,
+    Directory,
+}
+
+impl Wad {
+    /// Reads the header and tables of the wad at `path`.
+    pub fn open(path: &Path) -> Result<Self, WadError> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        Wad::read(BufReader::new(file), len)
+    }
+
+    /// Reads the header and tables from the start of `reader`, which holds `len` bytes in total.
+    pub fn read(reader: impl Read, len: u64) -> Result<Self, WadError> {
+        let mut reader = TableReader {
+            inner: reader,
+            position: 0,
+            len,
+        };
+
+        if reader.bytes(WAD_MAGIC.len(), "magic")? != WAD_MAGIC {
+            return Err(WadError::NotAWad);
+        }
+        let version = (reader.u32("version")?, reader.u32("version")?);
+        if version.0 != WAD_VERSION.0 {
+            return Err(WadError::UnsupportedVersion(version.0, version.1));
+        }
+        let extra_header_len = reader.u32("header")?;
+        let extra_header = reader.bytes(extra_header_len as usize, "header")?;
+
+        // The smallest file entry is an empty name, a size and an offset.
+        let entry_count = reader.count(4 + 8 + 8, "file table")?;
+        let mut entries = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            entries.push(WadEntry {
+                name: reader.string("file table")?,
+                size: reader.u64("file table")?,
+                offset: reader.u64("file table")?,
+            });
+        }
+
+        // The smallest directory is an empty name without children.
+        let directory_count = reader.count(4 + 4, "directory table")?;
+        let mut directories = Vec::with_capacity(directory_count);
+        for _ in 0..directory_count {
+            let name = reader.string("directory table")?;
+            let child_count = reader.count(4 + 1, "directory table")?;
+            let mut children = Vec::with_capacity(child_count);
+            for _ in 0..child_count {
+                let name = reader.string("directory table")?;
+                let kind = match reader.u8("directory table")? {
+                    0 => ChildKind::File,
+                    1 => ChildKind::Directory,
+                    kind => return Err(WadError::InvalidChildKind(name, kind)),
+                };
+                children.push(WadDirectoryChild { name, kind });
+            }
+            directories.push(WadDirectory { name, children });
+        }
+
+        Ok(Wad {
+            version,
+            extra_header,
+            entries,
+            directories,
+            data_offset: reader.position,
+        })
+    }
+
+    pub fn version(&self) -> (u32, u32) {
+        self.version
+    }
+
+    pub fn extra_header(&self) -> &[u8] {
+        &self.extra_header
+    }
+
+    pub fn entries(&self) -> &[WadEntry] {
+        &self.entries
+    }
+
+    pub fn directories(&self) -> &[WadDirectory] {
+        &self.directories
+    }
+
+    /// Where the file data starts, which the entry offsets are counted from.
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    pub fn find(&self, name: &str) -> Option<&WadEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Reads the bytes of one of the entries from the wad they came from.
+    pub fn read_entry<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        entry: &WadEntry,
+    ) -> Result<Vec<u8>, WadError> {
+        let mut bytes = Vec::new();
+        self.copy_entry(reader, entry, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// A reader over the bytes of one of the entries, for reading only part of them.
+    pub fn entry_reader<'a, R: Read + Seek>(
+        &self,
+        reader: &'a mut R,
+        entry: &WadEntry,
+    ) -> Result<io::Take<&'a mut R>, WadError> {
+        reader.seek(io::SeekFrom::Start(self.data_offset + entry.offset))?;
+
+        Ok(reader.take(entry.size))
+    }
+
+    /// Copies the bytes of one of the entries into `writer`, without holding them in memory.
+    pub fn copy_entry<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        entry: &WadEntry,
+        writer: &mut impl Write,
+    ) -> Result<(), WadError> {
+        reader.seek(io::SeekFrom::Start(self.data_offset + entry.offset))?;
+
+        if io::copy(&mut reader.take(entry.size), writer)? != entry.size {
+            return Err(WadError::Truncated("file data"));
+        }
+
+        Ok(())
+    }
+}
+
+impl WadEntry {
+    /// The path of the file inside the wad, like `Sounds/Hit.wav`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Where the data starts, counted from [`Wad::data_offset`].
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl WadDirectory {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn children(&self) -> &[WadDirectoryChild] {
+        &self.children
+    }
+}
+
+impl WadDirectoryChild {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> ChildKind {
+        self.kind
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum WadError {
+    #[error("Error reading the wad file. Error: {0}")]
+    Io(#[from] io::Error),
+    #[error("This isn't a wad file, it doesn't start with AGAR.")]
+    NotAWad,
+    #[error("The wad file uses version {0}.{1} of the format, which isn't supported.")]
+    UnsupportedVersion(u32, u32),
+    #[error("The wad file is truncated, it ends in the middle of its {0}.")]
+    Truncated(&'static str),
+    #[error("The wad file has a name that isn't valid text in its {0}.")]
+    InvalidName(&'static str),
+    #[error("The wad file lists {0} as a directory entry of unknown kind {1}.")]
+    InvalidChildKind(String, u8),
+    #[error("The wad file is truncated, {0} ends at byte {1} but the file only has {2} bytes.")]
+    EntryOutOfBounds(String, u64, u64),
+    #[error("The wad file is corrupt, the data of {0} and {1} overlap.")]
+    EntriesOverlap(String, String),
+    #[error("The wad file is corrupt, it lists {0} more than once.")]
+    DuplicateEntry(String),
+    #[error("The wad file has a file called {0}, which would be written outside the folder.")]
+    UnsafeName(String),
+    #[error(
+        "The wad file has a file called {0}, which is the name of the manifest unpacking writes."
+    )]
+    ReservedName(String),
+    #[error("{} isn't empty, unpack into a new folder.", .0.display())]
+    NotEmpty(Box<Path>),
+    #[error("{0} is listed in the manifest but isn't in the folder anymore.")]
+    MissingFile(String),
+    #[error("The manifest places data at byte {0} of the data section, but the data before it ends at byte {1}.")]
+    InvalidLayout(u64, u64),
+    #[error("There's no file called {0} in {}.", .1.display())]
+    MissingEntry(String, Box<Path>),
+    #[error("Error reading {}. Error: {0}", .1.display())]
+    ReadingFile(io::Error, Box<Path>),
+    #[error("The manifest has bytes that aren't valid hexadecimal.")]
+    InvalidHex,
+    #[error("The manifest could not be read. Error: {0}")]
+    InvalidManifest(#[from] toml::de::Error),
+    #[error("Error trying to format the manifest. Error: {0}")]
+    SerializingManifest(#[from] toml::ser::Error),
+}
+
+/// Reads the tables while keeping track of how many bytes are left, so a corrupt count fails
+/// instead of allocating more memory than the file could ever describe.
+struct TableReader<R> {
+    inner: R,
+    position: u64,
+    len: u64,
+}
+
+impl<R: Read> TableReader<R> {
+    fn bytes(&mut self, count: usize, section: &'static str) -> Result<Vec<u8>, WadError> {
+        if count as u64 > self.len.saturating_sub(self.position) {
+            return Err(WadError::Truncated(section));
+        }
+
+        let mut bytes = vec![0; count];
+        self.inner.read_exact(&mut bytes).map_err(|err| {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                WadError::Truncated(section)
+            } else {
+                WadError::Io(err)
+            }
+        })?;
+        self.position += count as u64;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self, section: &'static str) -> Result<[u8; N], WadError> {
+        let mut array = [0; N];
+        array.copy_from_slice(&self.bytes(N, section)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self, section: &'static str) -> Result<u8, WadError> {
+        Ok(self.array::<1>(section)?[0])
+    }
+
+    fn u32(&mut self, section: &'static str) -> Result<u32, WadError> {
+        Ok(u32::from_le_bytes(self.array(section)?))
+    }
+
+    fn u64(&mut self, section: &'static str) -> Result<u64, WadError> {
+        Ok(u64::from_le_bytes(self.array(section)?))
+    }
+
+    fn string(&mut self, section: &'static str) -> Result<String, WadError> {
+        let len = self.u32(section)?;
+        String::from_utf8(self.bytes(len as usize, section)?)
+            .map_err(|_| WadError::InvalidName(section))
+    }
+
+    /// Reads a count of items that take at least `min_item_len` bytes each.
+    fn count(&mut self, min_item_len: u64, section: &'static str) -> Result<usize, WadError> {
+        let count = self.u32(section)?;
+        if u64::from(count) * min_item_len > self.len.saturating_sub(self.position) {
+            return Err(WadError::Truncated(section));
+        }
+
+        Ok(count as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+        bytes.extend(value.to_le_bytes());
+    }
+
+    fn push_string(bytes: &mut Vec<u8>, value: &str) {
+        push_u32(bytes, u32::try_from(value.len()).unwrap());
+        bytes.extend(value.as_bytes());
+    }
+
+    /// A wad with `Music/Intro.ogg` and `Music/Outro.ogg`, listed in two directories.
+    fn wad_bytes() -> Vec<u8> {
+        let mut bytes = WAD_MAGIC.to_vec();
+        push_u32(&mut bytes, 1);
+        push_u32(&mut bytes, 1);
+        push_u32(&mut bytes, 2);
+        bytes.extend([0xAB, 0xCD]);
+
+        push_u32(&mut bytes, 2);
+        for (name, size, offset) in [("Music/Intro.ogg", 5u64, 0u64), ("Music/Outro.ogg", 3, 5)] {
+            push_string(&mut bytes, name);
+            bytes.extend(size.to_le_bytes());
+            bytes.extend(offset.to_le_bytes());
+        }
+
+        push_u32(&mut bytes, 2);
+        push_string(&mut bytes, "");
+        push_u32(&mut bytes, 1);
+        push_string(&mut bytes, "Music");
+        bytes.push(1);
+        push_string(&mut bytes, "Music");
+        push_u32(&mut bytes, 2);
+        push_string(&mut bytes, "Intro.ogg");
+        bytes.push(0);
+        push_string(&mut bytes, "Outro.ogg");
+        bytes.push(0);
+
+        bytes.extend(b"introout");
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> Result<Wad, WadError> {
+        Wad::read(bytes, bytes.len() as u64)
+    }
+
+    #[test]
+    fn reads_a_valid_wad() {
+        let bytes = wad_bytes();
+        let wad = read(&bytes).unwrap();
+
+        assert_eq!(wad.version(), WAD_VERSION);
+        assert_eq!(wad.extra_header(), [0xAB, 0xCD]);
+        assert_eq!(
+            wad.entries(),
+            [
+                WadEntry::new("Music/Intro.ogg".to_string(), 5, 0),
+                WadEntry::new("Music/Outro.ogg".to_string(), 3, 5),
+            ]
+        );
+        assert_eq!(
+            wad.directories(),
+            WadDirectory::listings_for(["Music/Intro.ogg", "Music/Outro.ogg"])
+        );
+        assert_eq!(wad.data_offset(), bytes.len() as u64 - 8);
+        wad.validate(bytes.len() as u64).unwrap();
+
+        let entry = wad.find("Music/Outro.ogg").unwrap();
+        let data = wad.read_entry(&mut io::Cursor::new(&bytes), entry).unwrap();
+        assert_eq!(data, b"out");
+    }
+
+    #[test]
+    fn rejects_a_truncated_header() {
+        let bytes = wad_bytes();
+
+        assert!(matches!(
+            read(&bytes[..2]),
+            Err(WadError::Truncated("magic"))
+        ));
+        assert!(matches!(
+            read(&bytes[..6]),
+            Err(WadError::Truncated("version"))
+        ));
+        assert!(matches!(
+            read(&bytes[..17]),
+            Err(WadError::Truncated("header"))
+        ));
+    }
+
+    #[test]
+    fn rejects_a_truncated_table() {
+        let bytes = wad_bytes();
+        let file_table = 18;
+        let directory_table = file_table + 4 + 2 * (4 + 15 + 8 + 8);
+
+        assert!(matches!(
+            read(&bytes[..file_table + 10]),
+            Err(WadError::Truncated("file table"))
+        ));
+        assert!(matches!(
+            read(&bytes[..directory_table + 10]),
+            Err(WadError::Truncated("directory table"))
+        ));
+    }
+
+    #[test]
+    fn rejects_a_corrupt_count() {
+        let mut bytes = wad_bytes();
+        bytes[18..22].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert!(matches!(
+            read(&bytes),
+            Err(WadError::Truncated("file table"))
+        ));
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = wad_bytes();
+        bytes[..4].copy_from_slice(b"RIFF");
+
+        assert!(matches!(read(&bytes), Err(WadError::NotAWad)));
+    }
+
+    #[test]
+    fn rejects_an_unsupported_version() {
+        let mut bytes = wad_bytes();
+        bytes[4..8].copy_from_slice(&2u32.to_le_bytes());
+
+        assert!(matches!(
+            read(&bytes),
+            Err(WadError::UnsupportedVersion(2, 1))
+        ));
+    }
+
+    #[test]
+    fn rejects_an_unknown_child_kind() {
+        let mut bytes = wad_bytes();
+        // The kind of the last child, right before the file data.
+        let kind = bytes.len() - 8 - 1;
+        bytes[kind] = 7;
+
+        assert!(matches!(
+            read(&bytes),
+            Err(WadError::InvalidChildKind(name, 7)) if name == "Outro.ogg"
+        ));
+    }
+
+    #[test]
+    fn written_tables_read_back_the_same() {
+        let names = ["Sprites/Hero.png", "Sounds/Hit.wav", "Readme.txt"];
+        let wad = Wad::new(
+            WAD_VERSION,
+            vec![1, 2, 3],
+            vec![
+                WadEntry::new(names[0].to_string(), 10, 0),
+                WadEntry::new(names[1].to_string(), 0, 10),
+                WadEntry::new(names[2].to_string(), 4, 10),
+            ],
+            WadDirectory::listings_for(names),
+        );
+
+        let mut bytes = Vec::new();
+        wad.write_tables(&mut bytes).unwrap();
+        assert_eq!(bytes.len() as u64, wad.data_offset());
+        bytes.extend([0; 14]);
+
+        assert_eq!(read(&bytes).unwrap(), wad);
+    }
+}
@@ -0,0 +1,367 @@
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::functions::write_atomically;
+
+use super::{
+    validate::validate_wad, write::write_wad_file, Wad, WadDirectory, WadEntry, WadError,
+    WAD_VERSION,
+};
+
+pub const WAD_MANIFEST_FILE_NAME: &str = "wad_manifest.toml";
+
+/// Everything about a wad that isn't in its files, so packing an unchanged folder gives back the
+/// exact same bytes.
+#[derive(Serialize, Deserialize, Debug)]
+struct Manifest {
+    version: (u32, u32),
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    extra_header: String,
+    #[serde(default)]
+    files: Vec<ManifestFile>,
+    #[serde(default)]
+    directories: Vec<WadDirectory>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    gaps: Vec<ManifestGap>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct ManifestFile {
+    name: String,
+    size: u64,
+    offset: u64,
+}
+
+/// Bytes in the data section that belong to no file, like padding.
+#[derive(Serialize, Deserialize, Debug)]
+struct ManifestGap {
+    offset: u64,
+    bytes: String,
+}
+
+/// A piece of the data section, in the order it's written.
+enum Chunk<'a> {
+    Entry(&'a WadEntry),
+    Gap(&'a ManifestGap),
+}
+
+/// Extracts every file of the wad into `dir`, which has to be empty or not exist yet, next to a
+/// manifest [`pack`] uses to rebuild it.
+pub fn unpack(wad_path: &Path, dir: &Path) -> Result<Wad, WadError> {
+    let wad = validate_wad(wad_path)?;
+    if fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(WadError::NotEmpty(dir.into()));
+    }
+    if let Some(entry) = wad.find(WAD_MANIFEST_FILE_NAME) {
+        return Err(WadError::ReservedName(entry.name().to_string()));
+    }
+
+    let mut file = File::open(wad_path)?;
+    for entry in wad.entries() {
+        let path = entry_path(dir, entry.name())?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut output = BufWriter::new(File::create(&path)?);
+        wad.copy_entry(&mut file, entry, &mut output)?;
+        output.flush()?;
+    }
+
+    let manifest = Manifest {
+        version: wad.version(),
+        extra_header: to_hex(wad.extra_header()),
+        files: wad
+            .entries()
+            .iter()
+            .map(|entry| ManifestFile {
+                name: entry.name().to_string(),
+                size: entry.size(),
+                offset: entry.offset(),
+            })
+            .collect(),
+        directories: wad.directories().to_vec(),
+        gaps: find_gaps(&wad, &mut file, fs::metadata(wad_path)?.len())?,
+    };
+    let contents = toml::to_string_pretty(&manifest)?;
+    fs::create_dir_all(dir)?;
+    write_atomically(&dir.join(WAD_MANIFEST_FILE_NAME), contents.as_bytes())?;
+
+    Ok(wad)
+}
+
+/// Builds a wad from the files in `dir`.
+///
+/// With the manifest [`unpack`] left, the files keep their order and an unchanged folder gives
+/// back the original bytes. Files added since are placed after the others, and once any size
+/// changed the data is laid out again without gaps. Without a manifest, the files are packed in
+/// name order.
+pub fn pack(dir: &Path, wad_path: &Path) -> Result<Wad, WadError> {
+    let manifest = match fs::read_to_string(dir.join(WAD_MANIFEST_FILE_NAME)) {
+        Ok(contents) => Some(toml::from_str::<Manifest>(&contents)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut on_disk = Vec::new();
+    list_files(dir, "", &mut on_disk)?;
+    on_disk.sort();
+
+    let known = manifest
+        .as_ref()
+        .map_or(&[][..], |manifest| &manifest.files[..]);
+    if let Some(missing) = known.iter().find(|file| !on_disk.contains(&file.name)) {
+        return Err(WadError::MissingFile(missing.name.clone()));
+    }
+    let added = on_disk
+        .iter()
+        .filter(|name| !known.iter().any(|file| file.name == **name))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut names_and_sizes = Vec::new();
+    for name in known.iter().map(|file| &file.name).chain(&added) {
+        let size = fs::metadata(entry_path(dir, name)?)?.len();
+        names_and_sizes.push((name.clone(), size));
+    }
+
+    let same_layout = manifest.is_some()
+        && added.is_empty()
+        && known
+            .iter()
+            .zip(&names_and_sizes)
+            .all(|(file, (_, size))| file.size == *size);
+
+    let mut next_offset = 0;
+    let entries = names_and_sizes
+        .iter()
+        .zip(known.iter().map(Some).chain(std::iter::repeat(None)))
+        .map(|((name, size), file)| {
+            let offset = match file {
+                Some(file) if same_layout => file.offset,
+                _ => next_offset,
+            };
+            next_offset += size;
+            WadEntry::new(name.clone(), *size, offset)
+        })
+        .collect::<Vec<_>>();
+
+    let (version, extra_header, directories, gaps) = match manifest {
+        Some(manifest) => (
+            manifest.version,
+            from_hex(&manifest.extra_header)?,
+            if added.is_empty() {
+                manifest.directories
+            } else {
+                WadDirectory::listings_for(entries.iter().map(WadEntry::name))
+            },
+            if same_layout {
+                manifest.gaps
+            } else {
+                Vec::new()
+            },
+        ),
+        None => (
+            WAD_VERSION,
+            Vec::new(),
+            WadDirectory::listings_for(entries.iter().map(WadEntry::name)),
+            Vec::new(),
+        ),
+    };
+
+    let wad = Wad::new(version, extra_header, entries, directories);
+    write_wad(&wad, dir, &gaps, wad_path)?;
+
+    Ok(wad)
+}
+
+fn write_wad(wad: &Wad, dir: &Path, gaps: &[ManifestGap], wad_path: &Path) -> Result<(), WadError> {
+    write_wad_file(wad_path, |output| {
+        wad.write_tables(output)?;
+
+        let mut chunks = wad
+            .entries()
+            .iter()
+            .map(|entry| (entry.offset(), Chunk::Entry(entry)))
+            .chain(gaps.iter().map(|gap| (gap.offset, Chunk::Gap(gap))))
+            .collect::<Vec<_>>();
+        chunks.sort_by_key(|(offset, _)| *offset);
+
+        let mut position = 0;
+        for (offset, chunk) in chunks {
+            if offset != position {
+                return Err(WadError::InvalidLayout(offset, position));
+            }
+            position += match chunk {
+                Chunk::Entry(entry) => {
+                    io::copy(&mut File::open(entry_path(dir, entry.name())?)?, output)?
+                }
+                Chunk::Gap(gap) => {
+                    let bytes = from_hex(&gap.bytes)?;
+                    output.write_all(&bytes)?;
+                    bytes.len() as u64
+                }
+            };
+        }
+
+        Ok(())
+    })
+}
+
+/// The bytes of the data section between and after the files.
+fn find_gaps(wad: &Wad, file: &mut File, len: u64) -> Result<Vec<ManifestGap>, WadError> {
+    let mut ranges = wad
+        .entries()
+        .iter()
+        .map(|entry| (entry.offset(), entry.offset() + entry.size()))
+        .collect::<Vec<_>>();
+    ranges.sort_unstable();
+    ranges.push((len - wad.data_offset(), len - wad.data_offset()));
+
+    let mut gaps = Vec::new();
+    let mut position = 0;
+    for (start, end) in ranges {
+        if start > position {
+            let gap = WadEntry::new(String::new(), start - position, position);
+            gaps.push(ManifestGap {
+                offset: position,
+                bytes: to_hex(&wad.read_entry(file, &gap)?),
+            });
+        }
+        position = position.max(end);
+    }
+
+    Ok(gaps)
+}
+
+/// Collects the files inside `dir` as wad names, separated by `/`.
+fn list_files(dir: &Path, prefix: &str, names: &mut Vec<String>) -> Result<(), WadError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name
+            .to_str()
+            .ok_or(WadError::InvalidName("folder being packed"))?;
+        let name = if prefix.is_empty() {
+            file_name.to_string()
+        } else {
+            format!("{prefix}/{file_name}")
+        };
+
+        if entry.file_type()?.is_dir() {
+            list_files(&entry.path(), &name, names)?;
+        } else if name != WAD_MANIFEST_FILE_NAME {
+            names.push(name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Where an entry goes inside `dir`, refusing names that would leave it.
+fn entry_path(dir: &Path, name: &str) -> Result<PathBuf, WadError> {
+    let mut path = dir.to_path_buf();
+    for part in name.split('/') {
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(component)), None) if !part.contains('\\') => {
+                path.push(component);
+            }
+            _ => return Err(WadError::UnsafeName(name.to_string())),
+        }
+    }
+
+    Ok(path)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, WadError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(WadError::InvalidHex);
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            hex.get(index..index + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or(WadError::InvalidHex)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wad::{ChildKind, WadDirectoryChild};
+
+    use super::*;
+
+    /// A wad with an extra header, directory listings, padding between its files and after them.
+    fn wad_bytes(names: [&str; 2]) -> Vec<u8> {
+        let wad = Wad::new(
+            WAD_VERSION,
+            vec![0xAB, 0xCD],
+            vec![
+                WadEntry::new(names[0].to_string(), 5, 0),
+                WadEntry::new(names[1].to_string(), 3, 8),
+            ],
+            vec![WadDirectory::new(
+                String::new(),
+                vec![WadDirectoryChild::new(
+                    "Music".to_string(),
+                    ChildKind::Directory,
+                )],
+            )],
+        );
+
+        let mut bytes = Vec::new();
+        wad.write_tables(&mut bytes).unwrap();
+        bytes.extend(b"intro\0\0\0out\xFF");
+        bytes
+    }
+
+    #[test]
+    fn unchanged_folder_packs_back_into_the_same_bytes() {
+        let root = tempfile::tempdir().unwrap();
+        let original = root.path().join("original.wad");
+        let packed = root.path().join("packed.wad");
+        let dir = root.path().join("unpacked");
+        let bytes = wad_bytes(["Music/Intro.ogg", "Music/Outro.ogg"]);
+        fs::write(&original, &bytes).unwrap();
+
+        unpack(&original, &dir).unwrap();
+        pack(&dir, &packed).unwrap();
+
+        assert_eq!(fs::read(packed).unwrap(), bytes);
+    }
+
+    #[test]
+    fn refuses_a_file_named_like_the_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        let original = root.path().join("original.wad");
+        let dir = root.path().join("unpacked");
+        fs::write(
+            &original,
+            wad_bytes(["Music/Intro.ogg", WAD_MANIFEST_FILE_NAME]),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            unpack(&original, &dir),
+            Err(WadError::ReservedName(name)) if name == WAD_MANIFEST_FILE_NAME
+        ));
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use super::{Wad, WadError};
+
+impl Wad {
+    /// Checks that every entry's data fits in a file of `len` bytes, and that no two entries
+    /// share a name or overlap.
+    pub fn validate(&self, len: u64) -> Result<(), WadError> {
+        let mut names = HashSet::new();
+        for entry in &self.entries {
+            if !names.insert(entry.name.as_str()) {
+                return Err(WadError::DuplicateEntry(entry.name.clone()));
+            }
+
+            let end = self
+                .data_offset
+                .checked_add(entry.offset)
+                .and_then(|start| start.checked_add(entry.size))
+                .unwrap_or(u64::MAX);
+            if end > len {
+                return Err(WadError::EntryOutOfBounds(entry.name.clone(), end, len));
+            }
+        }
+
+        let mut by_offset = self
+            .entries
+            .iter()
+            .filter(|entry| entry.size > 0)
+            .collect::<Vec<_>>();
+        by_offset.sort_by_key(|entry| entry.offset);
+        for pair in by_offset.windows(2) {
+            if pair[0].offset + pair[0].size > pair[1].offset {
+                return Err(WadError::EntriesOverlap(
+                    pair[0].name.clone(),
+                    pair[1].name.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads and validates the wad at `path`, catching truncated and corrupt files before they get
+/// installed.
+pub fn validate_wad(path: &Path) -> Result<Wad, WadError> {
+    let wad = Wad::open(path)?;
+    wad.validate(fs::metadata(path)?.len())?;
+
+    Ok(wad)
+}
+
+/// Whether the file is a `.wad` or a `.patchwad`, which are the files [`validate_wad`] can check.
+pub fn is_wad_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("wad") || extension.eq_ignore_ascii_case("patchwad")
+    }) && path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wad::{WadEntry, WAD_VERSION};
+
+    use super::*;
+
+    fn wad(entries: &[(&str, u64, u64)]) -> Wad {
+        Wad::new(
+            WAD_VERSION,
+            Vec::new(),
+            entries
+                .iter()
+                .map(|(name, size, offset)| WadEntry::new((*name).to_string(), *size, *offset))
+                .collect(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn accepts_entries_that_fit() {
+        let wad = wad(&[("a", 4, 0), ("empty", 0, 4), ("b", 6, 4)]);
+
+        wad.validate(wad.data_offset() + 10).unwrap();
+    }
+
+    #[test]
+    fn rejects_an_entry_out_of_bounds() {
+        let wad = wad(&[("a", 4, 0), ("b", 6, 4)]);
+        let len = wad.data_offset() + 9;
+
+        assert!(matches!(
+            wad.validate(len),
+            Err(WadError::EntryOutOfBounds(name, end, actual))
+                if name == "b" && end == len + 1 && actual == len
+        ));
+    }
+
+    #[test]
+    fn rejects_an_offset_that_overflows() {
+        let wad = wad(&[("a", 4, u64::MAX)]);
+
+        assert!(matches!(
+            wad.validate(1024),
+            Err(WadError::EntryOutOfBounds(_, u64::MAX, _))
+        ));
+    }
+
+    #[test]
+    fn rejects_overlapping_entries() {
+        let wad = wad(&[("b", 6, 3), ("a", 4, 0)]);
+
+        assert!(matches!(
+            wad.validate(wad.data_offset() + 10),
+            Err(WadError::EntriesOverlap(first, second)) if first == "a" && second == "b"
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicate_entries() {
+        let wad = wad(&[("a", 4, 0), ("a", 4, 4)]);
+
+        assert!(matches!(
+            wad.validate(wad.data_offset() + 8),
+            Err(WadError::DuplicateEntry(name)) if name == "a"
+        ));
+    }
+}
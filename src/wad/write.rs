@@ -0,0 +1,181 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use super::{ChildKind, Wad, WadDirectory, WadDirectoryChild, WadEntry, WadError, WAD_MAGIC};
+
+impl Wad {
+    /// Builds the header and tables of a new wad, with the file data starting right after them.
+    pub fn new(
+        version: (u32, u32),
+        extra_header: Vec<u8>,
+        entries: Vec<WadEntry>,
+        directories: Vec<WadDirectory>,
+    ) -> Self {
+        let mut wad = Wad {
+            version,
+            extra_header,
+            entries,
+            directories,
+            data_offset: 0,
+        };
+        wad.data_offset = wad.tables_len();
+
+        wad
+    }
+
+    /// Writes the header and tables, in the same layout [`Wad::read`] reads.
+    pub fn write_tables(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(WAD_MAGIC)?;
+        write_u32(writer, self.version.0)?;
+        write_u32(writer, self.version.1)?;
+        write_u32(writer, len_u32(self.extra_header.len())?)?;
+        writer.write_all(&self.extra_header)?;
+
+        write_u32(writer, len_u32(self.entries.len())?)?;
+        for entry in &self.entries {
+            write_string(writer, &entry.name)?;
+            writer.write_all(&entry.size.to_le_bytes())?;
+            writer.write_all(&entry.offset.to_le_bytes())?;
+        }
+
+        write_u32(writer, len_u32(self.directories.len())?)?;
+        for directory in &self.directories {
+            write_string(writer, &directory.name)?;
+            write_u32(writer, len_u32(directory.children.len())?)?;
+            for child in &directory.children {
+                write_string(writer, &child.name)?;
+                let kind: u8 = match child.kind {
+                    ChildKind::File => 0,
+                    ChildKind::Directory => 1,
+                };
+                writer.write_all(&[kind])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tables_len(&self) -> u64 {
+        let string_len = |value: &str| 4 + value.len() as u64;
+
+        let header = (WAD_MAGIC.len() + 4 + 4 + 4 + self.extra_header.len()) as u64;
+        let entries = 4 + self
+            .entries
+            .iter()
+            .map(|entry| string_len(&entry.name) + 8 + 8)
+            .sum::<u64>();
+        let directories = 4 + self
+            .directories
+            .iter()
+            .map(|directory| {
+                string_len(&directory.name)
+                    + 4
+                    + directory
+                        .children
+                        .iter()
+                        .map(|child| string_len(&child.name) + 1)
+                        .sum::<u64>()
+            })
+            .sum::<u64>();
+
+        header + entries + directories
+    }
+}
+
+impl WadEntry {
+    pub fn new(name: String, size: u64, offset: u64) -> Self {
+        WadEntry { name, size, offset }
+    }
+}
+
+impl WadDirectory {
+    pub fn new(name: String, children: Vec<WadDirectoryChild>) -> Self {
+        WadDirectory { name, children }
+    }
+
+    /// A listing for every folder the file names go through, the root being the empty name,
+    /// in the order they first appear.
+    pub fn listings_for<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<WadDirectory> {
+        let mut directories = Vec::<WadDirectory>::new();
+
+        for name in names {
+            let parts = name.split('/').collect::<Vec<_>>();
+            for (depth, child) in parts.iter().enumerate() {
+                let directory_name = parts[..depth].join("/");
+                let kind = if depth == parts.len() - 1 {
+                    ChildKind::File
+                } else {
+                    ChildKind::Directory
+                };
+
+                let index = directories
+                    .iter()
+                    .position(|directory| directory.name == directory_name)
+                    .unwrap_or_else(|| {
+                        directories.push(WadDirectory::new(directory_name, Vec::new()));
+                        directories.len() - 1
+                    });
+                let children = &mut directories[index].children;
+                if !children
+                    .iter()
+                    .any(|existing| existing.name == *child && existing.kind == kind)
+                {
+                    children.push(WadDirectoryChild::new((*child).to_string(), kind));
+                }
+            }
+        }
+
+        directories
+    }
+}
+
+impl WadDirectoryChild {
+    pub fn new(name: String, kind: ChildKind) -> Self {
+        WadDirectoryChild { name, kind }
+    }
+}
+
+/// Writes a wad through a temporary file next to `path` that's renamed over it once complete, so
+/// a failed write never leaves half a wad behind.
+pub fn write_wad_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), WadError>,
+) -> Result<(), WadError> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path)
+        .map_err(WadError::from)
+        .and_then(|file| {
+            let mut output = BufWriter::new(file);
+            write(&mut output)?;
+            output
+                .into_inner()
+                .map_err(io::IntoInnerError::into_error)?
+                .sync_all()?;
+            Ok(fs::rename(&temp_path, path)?)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> io::Result<()> {
+    write_u32(writer, len_u32(value.len())?)?;
+    writer.write_all(value.as_bytes())
+}
+
+fn len_u32(len: usize) -> io::Result<u32> {
+    u32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too long for a wad"))
+}
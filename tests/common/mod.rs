@@ -0,0 +1,131 @@
+//! A temporary game folder, mods folder and mods group folder shared by the tests.
+
+// Every test file uses only part of it.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use hotline_miami_mod_manager::{
+    configs::config_dir::{ConfigDir, ConfigDirSource},
+    manager::HotlineModManager,
+    mod_manager::ModManager,
+    prompter::{ScriptedAnswer, ScriptedPrompter},
+};
+use tempfile::TempDir;
+
+pub const MUSIC_FILE_NAME: &str = "hlm2_music_desktop.wad";
+
+/// A game folder with the original music, an empty mods folder and a mods group folder with one
+/// mod, `alpha`.
+pub struct Fixture {
+    root: TempDir,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let root = tempfile::tempdir().unwrap();
+        let fixture = Fixture { root };
+
+        fs::create_dir_all(fixture.game()).unwrap();
+        fs::create_dir_all(fixture.mods()).unwrap();
+        fs::create_dir_all(fixture.group().join("alpha/music")).unwrap();
+        fs::create_dir_all(fixture.group().join("alpha/mods")).unwrap();
+        fs::create_dir_all(fixture.config()).unwrap();
+
+        fs::write(
+            fixture.game().join(MUSIC_FILE_NAME),
+            wad(&[("Music/Track.ogg", b"original")]),
+        )
+        .unwrap();
+        fs::write(
+            fixture.group().join("alpha/music/alpha.wad"),
+            wad(&[("Music/Track.ogg", b"alpha music")]),
+        )
+        .unwrap();
+        fs::write(
+            fixture.group().join("alpha/mods/alpha.patchwad"),
+            wad(&[("Sprites/Hero.png", b"alpha hero")]),
+        )
+        .unwrap();
+
+        fixture
+    }
+
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    pub fn game(&self) -> PathBuf {
+        self.root().join("game")
+    }
+
+    pub fn mods(&self) -> PathBuf {
+        self.root().join("mods")
+    }
+
+    pub fn group(&self) -> PathBuf {
+        self.root().join("group")
+    }
+
+    pub fn config(&self) -> PathBuf {
+        self.root().join("config")
+    }
+
+    pub fn config_dir(&self) -> ConfigDir {
+        ConfigDir::new(self.config(), ConfigDirSource::Argument)
+    }
+
+    /// The answers to the three path prompts of the first run.
+    pub fn path_answers(&self) -> Vec<ScriptedAnswer> {
+        [self.game(), self.mods(), self.group()]
+            .into_iter()
+            .map(|path| ScriptedAnswer::Text(path.display().to_string()))
+            .collect()
+    }
+
+    /// Builds the mod manager, answering the first run's path prompts.
+    pub fn mod_manager(&self) -> ModManager {
+        let mut prompter = ScriptedPrompter::new(self.path_answers());
+        ModManager::build(self.config_dir(), &mut prompter).unwrap()
+    }
+
+    /// Runs the menu until it exits, with the given answers.
+    pub fn run(&self, answers: Vec<ScriptedAnswer>) {
+        let prompter = ScriptedPrompter::new(answers);
+        let mut manager =
+            HotlineModManager::with_prompter(self.config_dir(), Box::new(prompter)).unwrap();
+        manager.run();
+    }
+}
+
+/// The bytes of a wad holding the given files, without directory listings.
+pub fn wad(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut bytes = b"AGAR".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+
+    bytes.extend(u32::try_from(files.len()).unwrap().to_le_bytes());
+    let mut offset = 0u64;
+    for (name, data) in files {
+        bytes.extend(u32::try_from(name.len()).unwrap().to_le_bytes());
+        bytes.extend(name.as_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        offset += data.len() as u64;
+    }
+    bytes.extend(0u32.to_le_bytes());
+
+    for (_, data) in files {
+        bytes.extend(*data);
+    }
+
+    bytes
+}
+
+pub fn assert_same_file(left: &Path, right: &Path) {
+    assert_eq!(fs::read(left).unwrap(), fs::read(right).unwrap());
+}
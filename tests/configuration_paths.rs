@@ -0,0 +1,50 @@
+//! Changes the configured paths the way the menu and `config set` do.
+
+mod common;
+
+use std::fs;
+
+use hotline_miami_mod_manager::configs::{
+    paths_config::{ConfigPath, ConfigurationPath, ProgramPath},
+    Configs,
+};
+
+use common::Fixture;
+
+#[test]
+fn changed_path_is_saved() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+    let new_game = fixture.root().join("other_game");
+    fs::create_dir_all(&new_game).unwrap();
+
+    manager
+        .set_configuration_path(ConfigurationPath::Game(
+            ConfigPath::new().with_path(new_game.as_path()),
+        ))
+        .unwrap();
+
+    let configs = Configs::load(fixture.config_dir()).unwrap();
+    assert_eq!(configs.paths_config().game_path().path(), new_game);
+    assert_eq!(configs.paths_config().mods_path().path(), fixture.mods());
+}
+
+#[test]
+fn changed_path_is_restored_when_saving_fails() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+
+    // The configuration can't be written where a file stands in for its folder.
+    fs::remove_dir_all(fixture.config()).unwrap();
+    fs::write(fixture.config(), "").unwrap();
+
+    let result = manager.set_configuration_path(ConfigurationPath::Game(
+        ConfigPath::new().with_path(fixture.root().join("other_game")),
+    ));
+
+    assert!(result.is_err());
+    assert_eq!(
+        manager.configs().paths_config().game_path().path(),
+        fixture.game()
+    );
+}
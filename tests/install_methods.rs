@@ -0,0 +1,60 @@
+//! Installs mods with links instead of copies, like the `install_strategy` setting asks.
+
+mod common;
+
+use std::fs;
+
+use hotline_miami_mod_manager::{
+    configs::{config_file::CONFIG_FILE_NAME, install_receipt::InstallReceipt},
+    mod_manager::ModManager,
+    replace_mod::install_strategy::InstallMethod,
+};
+
+use common::{assert_same_file, Fixture};
+
+/// Switches to `alpha` with `method` and checks the receipt remembers how the patchwad got there.
+fn switch_with(method: InstallMethod) {
+    let fixture = Fixture::new();
+    fixture.mod_manager();
+    let config_file = fixture.config().join(CONFIG_FILE_NAME);
+    let contents = fs::read_to_string(&config_file).unwrap();
+    fs::write(
+        &config_file,
+        contents.replace(
+            "install_strategy = \"copy\"",
+            &format!("install_strategy = \"{method}\""),
+        ),
+    )
+    .unwrap();
+    let mut manager = ModManager::load(fixture.config_dir()).unwrap();
+    assert_eq!(manager.configs().settings().install_strategy(), method);
+
+    manager.switch_mod_by_name("alpha").unwrap();
+
+    let installed = fixture.mods().join("alpha.patchwad");
+    assert_same_file(
+        &installed,
+        &fixture.group().join("alpha/mods/alpha.patchwad"),
+    );
+    let receipt = InstallReceipt::read(&fixture.config_dir())
+        .unwrap()
+        .unwrap();
+    assert_eq!(receipt.method_of(&installed), method);
+
+    let survey = receipt.survey(vec![installed.clone()]).unwrap();
+    assert_eq!(survey.managed(), std::slice::from_ref(&installed));
+    assert!(survey.modified().is_empty());
+    assert!(survey.unmanaged().is_empty());
+    assert!(survey.can_replace(&installed));
+}
+
+#[test]
+fn hardlinked_install_is_recorded_and_managed() {
+    switch_with(InstallMethod::Hardlink);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_install_is_recorded_and_managed() {
+    switch_with(InstallMethod::Symlink);
+}
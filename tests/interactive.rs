@@ -0,0 +1,79 @@
+//! Runs the interactive flows with scripted answers instead of a terminal.
+
+mod common;
+
+use std::{fs, path::Path};
+
+use hotline_miami_mod_manager::{
+    configs::{paths_config::ProgramPath, Configs},
+    prompter::ScriptedAnswer,
+};
+
+use common::{assert_same_file, Fixture, MUSIC_FILE_NAME};
+
+#[test]
+fn first_run_asks_for_the_paths_and_saves_them() {
+    let fixture = Fixture::new();
+
+    let mut answers = vec![ScriptedAnswer::Text(
+        fixture.root().join("missing").display().to_string(),
+    )];
+    answers.extend(fixture.path_answers());
+    answers.push(ScriptedAnswer::Select("Exit.".to_string()));
+    fixture.run(answers);
+
+    let configs = Configs::load(fixture.config_dir()).unwrap();
+    let paths_config = configs.paths_config();
+    assert_eq!(paths_config.game_path().path(), fixture.game());
+    assert_eq!(paths_config.mods_path().path(), fixture.mods());
+    assert_eq!(paths_config.mods_group_path().path(), fixture.group());
+    assert!(configs.current_mod().is_none());
+}
+
+#[test]
+fn menu_switches_to_the_chosen_mod() {
+    let fixture = Fixture::new();
+
+    let mut answers = fixture.path_answers();
+    answers.extend([
+        ScriptedAnswer::Select("Change the current mod.".to_string()),
+        ScriptedAnswer::Select("Alpha".to_string()),
+        ScriptedAnswer::Confirm(true),
+        ScriptedAnswer::Select("Exit.".to_string()),
+    ]);
+    fixture.run(answers);
+
+    assert_same_file(
+        &fixture.game().join(MUSIC_FILE_NAME),
+        &fixture.group().join("alpha/music/alpha.wad"),
+    );
+    assert_same_file(
+        &fixture.mods().join("alpha.patchwad"),
+        &fixture.group().join("alpha/mods/alpha.patchwad"),
+    );
+
+    let configs = Configs::load(fixture.config_dir()).unwrap();
+    let current_mod = configs.current_mod().unwrap();
+    assert_eq!(current_mod.name().directory_name(), Path::new("alpha"));
+}
+
+#[test]
+fn menu_leaves_the_game_alone_when_the_switch_is_declined() {
+    let fixture = Fixture::new();
+    let original = fs::read(fixture.game().join(MUSIC_FILE_NAME)).unwrap();
+
+    let mut answers = fixture.path_answers();
+    answers.extend([
+        ScriptedAnswer::Select("Change the current mod.".to_string()),
+        ScriptedAnswer::Select("Alpha".to_string()),
+        ScriptedAnswer::Confirm(false),
+        ScriptedAnswer::Select("Exit.".to_string()),
+    ]);
+    fixture.run(answers);
+
+    assert_eq!(
+        fs::read(fixture.game().join(MUSIC_FILE_NAME)).unwrap(),
+        original
+    );
+    assert!(!fixture.mods().join("alpha.patchwad").exists());
+}
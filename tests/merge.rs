@@ -0,0 +1,49 @@
+//! Merges the patchwads of a mod, like the `merge` command does.
+
+mod common;
+
+use std::fs;
+
+use common::{wad, Fixture};
+
+#[test]
+fn failed_merge_puts_the_patchwads_back() {
+    let fixture = Fixture::new();
+    let mods_dir = fixture.group().join("alpha/mods");
+    fs::rename(
+        mods_dir.join("alpha.patchwad"),
+        mods_dir.join("gamma.patchwad"),
+    )
+    .unwrap();
+    fs::write(
+        mods_dir.join("beta.patchwad"),
+        wad(&[("Sprites/Enemy.png", b"beta enemy")]),
+    )
+    .unwrap();
+    // A folder where the merged patchwad goes keeps it from being moved in.
+    fs::create_dir_all(mods_dir.join("alpha.patchwad/taken")).unwrap();
+    let mut manager = fixture.mod_manager();
+
+    assert!(manager.merge_patchwads("alpha", &[]).is_err());
+
+    let mut left = fs::read_dir(fixture.group().join("alpha"))
+        .unwrap()
+        .chain(fs::read_dir(&mods_dir).unwrap())
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    left.sort();
+    assert_eq!(
+        left,
+        [
+            "alpha.patchwad",
+            "beta.patchwad",
+            "gamma.patchwad",
+            "mods",
+            "music"
+        ]
+    );
+    assert_eq!(
+        fs::read(mods_dir.join("beta.patchwad")).unwrap(),
+        wad(&[("Sprites/Enemy.png", b"beta enemy")])
+    );
+}
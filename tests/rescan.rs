@@ -0,0 +1,61 @@
+//! Picks up changes to the mods group folder made while the manager is open.
+
+mod common;
+
+use std::{fs, path::Path};
+
+use hotline_miami_mod_manager::mod_manager::AllMods;
+
+use common::{assert_same_file, wad, Fixture};
+
+/// The folder names of every mod in the library, in order.
+fn mod_names(all_mods: &AllMods) -> Vec<String> {
+    let mut names = all_mods
+        .mods()
+        .iter()
+        .map(|hm_mod| hm_mod.name().directory_name().display().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+#[test]
+fn rescan_finds_a_mod_added_on_disk() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+    assert_eq!(mod_names(manager.all_mods()), ["alpha"]);
+
+    let beta = fixture.group().join("beta");
+    fs::create_dir_all(beta.join("mods")).unwrap();
+    fs::write(
+        beta.join("mods/beta.patchwad"),
+        wad(&[("Sprites/Hero.png", b"beta hero")]),
+    )
+    .unwrap();
+    assert!(manager.switch_mod_by_name("beta").is_err());
+
+    let all_mods = manager.rescan_mods().unwrap();
+
+    assert_eq!(mod_names(all_mods), ["alpha", "beta"]);
+    manager.switch_mod_by_name("beta").unwrap();
+    assert_same_file(
+        &fixture.mods().join("beta.patchwad"),
+        &beta.join("mods/beta.patchwad"),
+    );
+    assert_eq!(
+        manager.current_mod().unwrap().name().directory_name(),
+        Path::new("beta")
+    );
+}
+
+#[test]
+fn rescan_drops_a_mod_removed_on_disk() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+
+    fs::remove_dir_all(fixture.group().join("alpha")).unwrap();
+
+    assert!(manager.rescan_mods().unwrap().mods().is_empty());
+    assert!(manager.switch_mod_by_name("alpha").is_err());
+}
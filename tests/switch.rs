@@ -0,0 +1,115 @@
+//! Switches mods without prompts, like the `switch` command does.
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{assert_same_file, wad, Fixture, MUSIC_FILE_NAME};
+
+/// Every file under `dir` with its contents, in name order.
+fn snapshot(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(snapshot(&path));
+        } else {
+            files.push((path.display().to_string(), fs::read(&path).unwrap()));
+        }
+    }
+    files.sort();
+
+    files
+}
+
+#[test]
+fn switch_warns_about_conflicting_patchwads() {
+    let fixture = Fixture::new();
+    fs::write(
+        fixture.group().join("alpha/mods/beta.patchwad"),
+        wad(&[("Sprites/Hero.png", b"beta hero")]),
+    )
+    .unwrap();
+    let mut manager = fixture.mod_manager();
+
+    let report = manager.switch_mod_by_name("alpha").unwrap();
+
+    let conflicts = report.conflicts().conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].asset(), "Sprites/Hero.png");
+    assert!(report
+        .warnings()
+        .iter()
+        .any(|warning| warning.starts_with("Conflict: Sprites/Hero.png")));
+}
+
+#[test]
+fn planning_track_music_writes_nothing() {
+    let fixture = Fixture::new();
+    let tracks_dir = fixture.group().join("gamma/music_tracks");
+    fs::create_dir_all(&tracks_dir).unwrap();
+    fs::write(tracks_dir.join("Track.ogg"), b"gamma track").unwrap();
+    let mut manager = fixture.mod_manager();
+    let before = snapshot(fixture.root());
+
+    let plan = manager.plan_switch_by_name("gamma").unwrap();
+
+    assert_eq!(snapshot(fixture.root()), before);
+    assert!(plan.music_changes());
+    let planned_size = plan.music().unwrap().size();
+
+    manager.switch_mod_by_name("gamma").unwrap();
+    let music = fs::read(fixture.game().join(MUSIC_FILE_NAME)).unwrap();
+    assert_eq!(music, wad(&[("Music/Track.ogg", b"gamma track")]));
+    assert_eq!(planned_size, music.len() as u64);
+}
+
+#[test]
+fn switching_again_copies_only_what_changed() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+    manager.switch_mod_by_name("alpha").unwrap();
+
+    let plan = manager.plan_switch_by_name("alpha").unwrap();
+
+    assert!(!plan.music_changes());
+    assert!(plan.copies().is_empty());
+    assert_eq!(plan.skips().len(), 1);
+    assert!(plan.deletes().is_empty());
+    assert_eq!(plan.bytes_written(), 0);
+
+    let source = fixture.group().join("alpha/mods/alpha.patchwad");
+    let installed = fixture.mods().join("alpha.patchwad");
+    fs::write(&source, wad(&[("Sprites/Hero.png", b"edited alpha hero")])).unwrap();
+
+    let plan = manager.plan_switch_by_name("alpha").unwrap();
+
+    assert!(plan.skips().is_empty());
+    let [copy] = plan.copies() else {
+        panic!("Expected one copy, found {:?}", plan.copies());
+    };
+    assert_eq!(copy.destination(), installed);
+    assert_eq!(plan.blocked_by(), None);
+
+    manager.switch_mod_by_name("alpha").unwrap();
+    assert_same_file(&installed, &source);
+}
+
+#[test]
+fn switching_again_keeps_an_installed_file_the_user_changed() {
+    let fixture = Fixture::new();
+    let mut manager = fixture.mod_manager();
+    manager.switch_mod_by_name("alpha").unwrap();
+
+    let installed = fixture.mods().join("alpha.patchwad");
+    let edited = wad(&[("Sprites/Hero.png", b"user's hero")]);
+    fs::write(&installed, &edited).unwrap();
+
+    let plan = manager.plan_switch_by_name("alpha").unwrap();
+
+    assert!(plan.skips().is_empty());
+    assert_eq!(plan.modified(), std::slice::from_ref(&installed));
+    assert_eq!(plan.blocked_by(), Some(installed.as_path()));
+    assert!(manager.switch_mod_by_name("alpha").is_err());
+    assert_eq!(fs::read(&installed).unwrap(), edited);
+}
@@ -0,0 +1,63 @@
+//! Undoes a switch that fails halfway through, like the switch commands do.
+
+mod common;
+
+use std::{fs, path::Path};
+
+use hotline_miami_mod_manager::replace_mod::transaction::{staging_dir, SwitchTransaction};
+
+use common::{assert_same_file, wad, Fixture, MUSIC_FILE_NAME};
+
+/// The names in `dir`, in order.
+fn names_in(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+#[test]
+fn failed_apply_puts_the_previous_files_back() {
+    let fixture = Fixture::new();
+    let beta = fixture.group().join("beta");
+    fs::create_dir_all(beta.join("music")).unwrap();
+    fs::create_dir_all(beta.join("mods")).unwrap();
+    fs::write(
+        beta.join("music/beta.wad"),
+        wad(&[("Music/Track.ogg", b"beta music")]),
+    )
+    .unwrap();
+    fs::write(
+        beta.join("mods/beta.patchwad"),
+        wad(&[("Sprites/Hero.png", b"beta hero")]),
+    )
+    .unwrap();
+    let mut manager = fixture.mod_manager();
+    manager.switch_mod_by_name("alpha").unwrap();
+
+    let plan = manager.plan_switch_by_name("beta").unwrap();
+    let mut transaction = SwitchTransaction::stage(&plan).unwrap();
+    // The music goes in first, then the staged patchwad is gone when its turn comes.
+    fs::remove_dir_all(staging_dir(&fixture.mods())).unwrap();
+
+    assert!(transaction.apply().is_err());
+    assert_same_file(
+        &fixture.game().join(MUSIC_FILE_NAME),
+        &beta.join("music/beta.wad"),
+    );
+    transaction.rollback().unwrap();
+
+    assert_same_file(
+        &fixture.game().join(MUSIC_FILE_NAME),
+        &fixture.group().join("alpha/music/alpha.wad"),
+    );
+    assert_same_file(
+        &fixture.mods().join("alpha.patchwad"),
+        &fixture.group().join("alpha/mods/alpha.patchwad"),
+    );
+    assert_eq!(names_in(&fixture.game()), [MUSIC_FILE_NAME]);
+    assert_eq!(names_in(&fixture.mods()), ["alpha.patchwad"]);
+}